@@ -43,7 +43,29 @@ fn main() {
     println!("cargo:warning=Backup version info written to: {}", root_version_path.display());
     println!("cargo:warning=Using Beijing time (UTC+8): {}", datetime);
     
+    // 单元测试编译为独立可执行文件，不能像扩展模块那样由解释器提供Python符号，需要链接libpython
+    link_python_for_tests(&out_dir);
+    
     // 强制每次构建时重新运行此脚本
     println!("cargo:rerun-if-changed=build.rs");
     println!("cargo:rerun-if-changed=src/lib.rs");
 }
+
+/// 生成只在单元测试中引入的libpython链接声明（扩展模块本身不链接libpython，由解释器提供符号）
+///
+/// 解释器取PYO3_PYTHON，默认python3；找不到时生成空文件，只有用到Python的测试会链接失败
+fn link_python_for_tests(out_dir: &str) {
+    println!("cargo:rerun-if-env-changed=PYO3_PYTHON");
+    let python = env::var("PYO3_PYTHON").unwrap_or_else(|_| "python3".to_string());
+    let script = "import sysconfig; print(sysconfig.get_config_var('LIBDIR')); print(sysconfig.get_config_var('LDVERSION'))";
+    let mut declaration = String::new();
+    if let Ok(output) = std::process::Command::new(&python).args(["-c", script]).output() {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut lines = stdout.lines();
+        if let (true, Some(lib_dir), Some(version)) = (output.status.success(), lines.next(), lines.next()) {
+            println!("cargo:rustc-link-search=native={}", lib_dir);
+            declaration = format!("#[link(name = \"python{}\")]\nextern \"C\" {{}}\n", version);
+        }
+    }
+    std::fs::write(Path::new(out_dir).join("link_python.rs"), declaration).unwrap();
+}
@@ -1,19 +1,274 @@
 use pyo3::prelude::*;
-use pyo3::wrap_pyfunction;
-use std::sync::{Arc, Mutex};
-use std::sync::atomic::{AtomicU64, AtomicBool, Ordering};
-use std::time::{Instant, Duration};
-use rayon::prelude::*;
-use std::collections::HashSet;
+use pyo3::exceptions::PyValueError;
+use pyo3::types::{PyBool, PyBytes, PyLong};
+use std::cell::{Cell, RefCell};
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::sync::atomic::{AtomicU64, AtomicBool, AtomicU8, AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, SyncSender};
+use std::time::{Duration, Instant};
+
+pyo3::create_exception!(
+    subset_sum,
+    SubsetSumError,
+    PyValueError,
+    "求解器错误，code属性为与语言无关的错误码"
+);
+
+/// 错误消息使用的语言，整个模块共享：0为中文（默认），1为英文
+static MESSAGE_LANGUAGE: AtomicU8 = AtomicU8::new(0);
+
+/// Python接口的版本号，只在不兼容地修改已有方法（参数含义、返回格式）时递增；新增功能只更新CAPABILITIES
+const API_VERSION: u32 = 1;
+
+/// 本构建支持的功能，供调用者在运行时检测；新增求解模式时在此登记
+const CAPABILITIES: &[&str] = &[
+    "negatives",      // find_subsets 接受负数输入（小规模走位运算，否则走有符号回溯）
+    "float",          // find_subsets_float 系列：浮点输入按小数位数缩放
+    "approx",         // find_subsets_approx：绝对/相对容差
+    "rational",       // find_subsets_fraction：fractions.Fraction精确求解
+    "streaming",      // find_subsets_float_iter（可迭代输入）与 paginate（分页输出）
+    "range",          // find_subsets_in_range 系列与 find_subsets_under
+    "near_misses",    // find_subsets_with_near_misses：精确解附带近似建议
+    "grouped",        // find_subsets_grouped：每组恰好选一个
+    "anchor",         // find_subsets_with_anchor / find_subsets_by_anchor
+    "fifo",           // find_subsets_fifo
+    "time_windows",   // find_subsets_timevalid
+    "ranking",        // find_subsets_by_preference / find_subsets_by_score
+    "partition",      // find_balanced_partition / allocate_to_buckets
+    "statistics",     // subset_statistics / index_frequency
+    "breaking_set",   // min_breaking_set
+    "witness",        // find_witness：只用回指表的单解动态规划
+    "online",         // OnlineSolver：数字逐个到达时的增量可达性判断
+    "interruptible",  // Ctrl-C中断与set_partial_on_interrupt
+];
+
+/// 求解器的所有错误，转换为Python的SubsetSumError（ValueError的子类）
+///
+/// code()是稳定的机器可读错误码，message()按模块语言设置生成中文或英文消息
+#[derive(Debug, Clone, PartialEq)]
+enum SolverError {
+    UnsupportedOption { option: &'static str, value: String },
+    NonFiniteInput,
+    ScaledOverflow { value: f64 },
+    ScaledToZero { value: f64, decimals: u32 },
+    TargetPrecision { target: f64, decimals: u32 },
+    InvalidStackSize,
+    BitmaskThresholdTooLarge { max: usize },
+    IndexOutOfRange { index: usize, len: usize },
+    DuplicateIndex { index: usize },
+    SumOverflow,
+    TargetTooLarge,
+    MemoryLimit { bytes: usize },
+    NegativeInput { index: usize, value: i64 },
+    LengthMismatch { numbers: usize, other: usize },
+    RationalOverflow,
+    BoolInput,
+    SolverPanic { message: String },
+}
+
+impl SolverError {
+    fn code(&self) -> &'static str {
+        match self {
+            SolverError::UnsupportedOption { .. } => "UNSUPPORTED_OPTION",
+            SolverError::NonFiniteInput => "NON_FINITE_INPUT",
+            SolverError::ScaledOverflow { .. } => "SCALED_OVERFLOW",
+            SolverError::ScaledToZero { .. } => "SCALED_TO_ZERO",
+            SolverError::TargetPrecision { .. } => "TARGET_PRECISION",
+            SolverError::InvalidStackSize => "INVALID_STACK_SIZE",
+            SolverError::BitmaskThresholdTooLarge { .. } => "BITMASK_THRESHOLD_TOO_LARGE",
+            SolverError::IndexOutOfRange { .. } => "INDEX_OUT_OF_RANGE",
+            SolverError::DuplicateIndex { .. } => "DUPLICATE_INDEX",
+            SolverError::SumOverflow => "SUM_OVERFLOW",
+            SolverError::TargetTooLarge => "TARGET_TOO_LARGE",
+            SolverError::MemoryLimit { .. } => "MEMORY_LIMIT",
+            SolverError::NegativeInput { .. } => "NEGATIVE_INPUT",
+            SolverError::LengthMismatch { .. } => "LENGTH_MISMATCH",
+            SolverError::RationalOverflow => "RATIONAL_OVERFLOW",
+            SolverError::BoolInput => "BOOL_INPUT",
+            SolverError::SolverPanic { .. } => "INTERNAL_ERROR",
+        }
+    }
+
+    fn message(&self) -> String {
+        let english = MESSAGE_LANGUAGE.load(Ordering::Relaxed) == 1;
+        match (self, english) {
+            (SolverError::UnsupportedOption { option, value }, false) => format!("不支持的{}: {}", option_name_zh(option), value),
+            (SolverError::UnsupportedOption { option, value }, true) => format!("unsupported {}: {}", option, value),
+            (SolverError::NonFiniteInput, false) => "输入包含NaN或无穷大".to_string(),
+            (SolverError::NonFiniteInput, true) => "input contains NaN or infinity".to_string(),
+            (SolverError::ScaledOverflow { value }, false) => format!("数值 {} 缩放后超出整数范围", value),
+            (SolverError::ScaledOverflow { value }, true) => format!("value {} is out of integer range after scaling", value),
+            (SolverError::ScaledToZero { value, decimals }, false) => format!("数值 {} 太小，按 {} 位小数缩放后变为0", value, decimals),
+            (SolverError::ScaledToZero { value, decimals }, true) => format!("value {} is too small and becomes 0 when scaled to {} decimal places", value, decimals),
+            (SolverError::TargetPrecision { target, decimals }, false) => format!("目标值 {} 的小数位数超过指定的 {} 位，缩放后无法精确表示", target, decimals),
+            (SolverError::TargetPrecision { target, decimals }, true) => format!("target {} has more than the requested {} decimal places and cannot be scaled exactly", target, decimals),
+            (SolverError::InvalidStackSize, false) => "栈大小必须大于0".to_string(),
+            (SolverError::InvalidStackSize, true) => "stack size must be greater than 0".to_string(),
+            (SolverError::BitmaskThresholdTooLarge { max }, false) => format!("位运算阈值不能超过 {}", max),
+            (SolverError::BitmaskThresholdTooLarge { max }, true) => format!("bitmask threshold cannot exceed {}", max),
+            (SolverError::IndexOutOfRange { index, len }, false) => format!("索引{}超出范围，数字个数为{}", index, len),
+            (SolverError::IndexOutOfRange { index, len }, true) => format!("index {} is out of range for {} numbers", index, len),
+            (SolverError::DuplicateIndex { index }, false) => format!("索引{}重复", index),
+            (SolverError::DuplicateIndex { index }, true) => format!("index {} is repeated", index),
+            (SolverError::SumOverflow, false) => "数字之和超出i64范围".to_string(),
+            (SolverError::SumOverflow, true) => "sum of numbers exceeds the i64 range".to_string(),
+            (SolverError::TargetTooLarge, false) => "目标值过大，无法建立位集".to_string(),
+            (SolverError::TargetTooLarge, true) => "target is too large to build a bitset".to_string(),
+            (SolverError::MemoryLimit { bytes }, false) => format!("需要{}字节，超出内存限制", bytes),
+            (SolverError::MemoryLimit { bytes }, true) => format!("{} bytes required, exceeding the memory limit", bytes),
+            (SolverError::NegativeInput { index, value }, false) => format!("只支持非负数，索引{}的值为{}", index, value),
+            (SolverError::NegativeInput { index, value }, true) => format!("only non-negative numbers are supported, index {} is {}", index, value),
+            (SolverError::LengthMismatch { numbers, other }, false) => format!("数字个数为{}，对应的列表长度为{}，两者必须相同", numbers, other),
+            (SolverError::LengthMismatch { numbers, other }, true) => format!("got {} numbers but a parallel list of length {}, they must match", numbers, other),
+            (SolverError::RationalOverflow, false) => "分数通分后的分子或公分母超出i64范围".to_string(),
+            (SolverError::RationalOverflow, true) => "numerators or the common denominator exceed the i64 range after scaling fractions".to_string(),
+            (SolverError::BoolInput, false) => "输入包含布尔值（True/False），请传入int或float".to_string(),
+            (SolverError::BoolInput, true) => "input contains a bool (True/False), pass int or float instead".to_string(),
+            (SolverError::SolverPanic { message }, false) => format!("求解过程中发生内部错误: {}", message),
+            (SolverError::SolverPanic { message }, true) => format!("internal error during solving: {}", message),
+        }
+    }
+}
+
+/// UnsupportedOption中选项名的中文写法
+fn option_name_zh(option: &str) -> &'static str {
+    match option {
+        "integer width" => "整数宽度",
+        "rounding mode" => "舍入方式",
+        "memory ordering" => "内存序",
+        "language" => "语言",
+        "tolerance" => "容差",
+        "denominator" => "分母",
+        "divisor" => "除数",
+        "scorer" => "评分方式",
+        "selection" => "选择方式",
+        _ => "选项",
+    }
+}
+
+impl From<SolverError> for PyErr {
+    fn from(err: SolverError) -> PyErr {
+        let py_err = SubsetSumError::new_err(err.message());
+        Python::with_gil(|py| {
+            // 设置属性失败时仍然返回带消息的异常
+            let _ = py_err.value(py).setattr("code", err.code());
+        });
+        py_err
+    }
+}
+
+/// 设置错误消息的语言："zh"（默认）或 "en"，对整个模块生效；错误码不受影响
+#[pyfunction]
+fn set_language(language: &str) -> PyResult<()> {
+    let value = match language.to_ascii_lowercase().as_str() {
+        "zh" => 0,
+        "en" => 1,
+        _ => return Err(SolverError::UnsupportedOption { option: "language", value: language.to_string() }.into()),
+    };
+    MESSAGE_LANGUAGE.store(value, Ordering::Relaxed);
+    Ok(())
+}
+
+/// 获取错误消息当前使用的语言
+#[pyfunction]
+fn get_language() -> &'static str {
+    if MESSAGE_LANGUAGE.load(Ordering::Relaxed) == 1 { "en" } else { "zh" }
+}
+
+/// 返回本构建支持的功能名称列表（如 "float"、"rational"、"streaming"），用于功能检测而不必依赖try/except
+///
+/// 列表只会随版本增加；模块属性API_VERSION在接口发生不兼容变化时递增
+#[pyfunction]
+fn capabilities() -> Vec<&'static str> {
+    CAPABILITIES.to_vec()
+}
 
 /// 算法类型枚举，用于智能算法选择
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Algorithm {
     BitManipulation,    // 位运算算法 - 适用于小规模问题
     DynamicProgramming, // 动态规划算法 - 适用于中等规模问题
     BacktrackingCompact, // 内存优化回溯算法 - 适用于大规模问题
 }
 
+impl Algorithm {
+    /// 算法的名称，用于对外报告
+    fn name(self) -> &'static str {
+        match self {
+            Algorithm::BitManipulation => "bitmask",
+            Algorithm::DynamicProgramming => "dp",
+            Algorithm::BacktrackingCompact => "backtracking",
+        }
+    }
+}
+
+/// 位运算算法支持的最大元素个数（掩码使用u64，同时限制2^n的枚举量）
+///
+/// 各策略支持的元素个数：位运算不超过MAX_BITMASK_N，超出时退回动态规划；
+/// 动态规划与回溯不限制n，但2^n超出u64（n >= 64）时进度中的组合总数饱和为u64::MAX
+const MAX_BITMASK_N: usize = 32;
+
+/// 含n个元素的集合的子集总数2^n，n >= 64时饱和为u64::MAX
+///
+/// 所有2^n的计算都应通过这里（或subset_count_u128），不要直接移位
+fn subset_count(n: usize) -> u64 {
+    if n >= u64::BITS as usize {
+        u64::MAX
+    } else {
+        1u64 << n
+    }
+}
+
+/// 同subset_count，用u128计算，n >= 128时饱和为u128::MAX
+fn subset_count_u128(n: usize) -> u128 {
+    if n >= u128::BITS as usize {
+        u128::MAX
+    } else {
+        1u128 << n
+    }
+}
+
+/// 算法选择的主要分界点
+#[derive(Debug, Clone, Copy)]
+struct StrategyThresholds {
+    bitmask_max_n: usize, // 不超过该数量时使用位运算
+    dp_max_n: usize,      // 不超过该数量且目标值不超过dp_max_target时使用动态规划
+    dp_max_target: i64,
+}
+
+impl Default for StrategyThresholds {
+    fn default() -> Self {
+        StrategyThresholds {
+            bitmask_max_n: 25,
+            dp_max_n: 100,
+            dp_max_target: 10000,
+        }
+    }
+}
+
+/// 可复现的伪随机数生成器（SplitMix64）
+/// 所有需要随机性的功能都从求解器种子派生，保证同一种子下结果完全一致
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
 /// 优化：压缩表示，使用位图表示子集
 struct CompactSubset {
     bitmap: Vec<u64>,
@@ -21,10 +276,15 @@ struct CompactSubset {
 }
 
 /// 内存对象池，避免频繁分配内存
+///
+/// 计数器默认使用Relaxed内存序：计数器只用于限额判断和统计，不用于同步其他数据。
+/// 限额检查与计数在同一次原子读改写中完成，并发分配时已用内存也不会超过限额
 #[pyclass]
 struct MemoryTracker {
     max_memory: usize,
     used_memory: Arc<AtomicU64>,
+    peak_memory: Arc<AtomicU64>, // 已用内存的历史最大值
+    ordering: Ordering,
 }
 
 impl MemoryTracker {
@@ -32,24 +292,41 @@ impl MemoryTracker {
         MemoryTracker {
             max_memory,
             used_memory: Arc::new(AtomicU64::new(0)),
+            peak_memory: Arc::new(AtomicU64::new(0)),
+            ordering: Ordering::Relaxed,
         }
     }
 
+    /// 读操作使用的内存序（Release/AcqRel对load无效，需要降级为Acquire）
+    fn load_ordering(&self) -> Ordering {
+        match self.ordering {
+            Ordering::AcqRel | Ordering::Release => Ordering::Acquire,
+            ordering => ordering,
+        }
+    }
+
+    /// 申请size字节的额度，超出限额时不计数并返回false，此时调用者不应再调用deallocate
     fn allocate(&self, size: usize) -> bool {
-        let current = self.used_memory.load(Ordering::SeqCst) as usize;
-        if current + size > self.max_memory {
-            return false;
+        let max_memory = self.max_memory as u64;
+        let updated = self.used_memory.fetch_update(self.ordering, self.load_ordering(), |current| {
+            current.checked_add(size as u64).filter(|&total| total <= max_memory)
+        });
+        match updated {
+            Ok(previous) => {
+                self.peak_memory.fetch_max(previous + size as u64, self.ordering);
+                true
+            }
+            Err(_) => false,
         }
-        self.used_memory.fetch_add(size as u64, Ordering::SeqCst);
-        true
     }
 
     fn deallocate(&self, size: usize) {
-        self.used_memory.fetch_sub(size as u64, Ordering::SeqCst);
+        let previous = self.used_memory.fetch_sub(size as u64, self.ordering);
+        debug_assert!(previous >= size as u64, "释放的内存 {} 多于已登记的 {}", size, previous);
     }
 
     fn get_used_memory(&self) -> usize {
-        self.used_memory.load(Ordering::SeqCst) as usize
+        self.used_memory.load(self.load_ordering()) as usize
     }
 }
 
@@ -58,11 +335,6 @@ impl CompactSubset {
         Self { bitmap: vec![0], count: 0 }
     }
     
-    fn with_capacity(max_size: usize) -> Self {
-        let blocks = (max_size + 63) / 64;
-        Self { bitmap: vec![0; blocks], count: 0 }
-    }
-    
     fn add(&mut self, index: usize) {
         let block_idx = index / 64;
         let bit_idx = index % 64;
@@ -130,405 +402,3417 @@ impl CompactSubset {
         
         result
     }
-    
-    fn clone(&self) -> Self {
-        Self {
-            bitmap: self.bitmap.clone(),
-            count: self.count,
-        }
-    }
 }
 
-// 对象池实现
-static mut SUBSET_POOL: Option<Vec<CompactSubset>> = None;
+// 对象池实现（每个线程独立，避免跨线程共享可变状态）
+thread_local! {
+    static SUBSET_POOL: RefCell<Vec<CompactSubset>> = RefCell::new(Vec::with_capacity(10));
+}
 
 fn get_compact_subset_from_pool() -> CompactSubset {
-    unsafe {
-        if SUBSET_POOL.is_none() {
-            SUBSET_POOL = Some(Vec::with_capacity(10));
-        }
-        
-        if let Some(pool) = &mut SUBSET_POOL {
-            if let Some(mut subset) = pool.pop() {
-                subset.clear();
-                return subset;
-            }
+    SUBSET_POOL.with(|pool| {
+        if let Some(mut subset) = pool.borrow_mut().pop() {
+            subset.clear();
+            return subset;
         }
-        
         CompactSubset::new()
-    }
+    })
 }
 
 fn return_compact_subset_to_pool(subset: CompactSubset) {
-    unsafe {
-        if SUBSET_POOL.is_none() {
-            SUBSET_POOL = Some(Vec::with_capacity(10));
+    SUBSET_POOL.with(|pool| {
+        let mut pool = pool.borrow_mut();
+        if pool.len() < 100 {  // 限制池大小
+            pool.push(subset);
         }
-        
-        if let Some(pool) = &mut SUBSET_POOL {
-            if pool.len() < 100 {  // 限制池大小
-                pool.push(subset);
+    })
+}
+
+/// solve_within的截止时间，只设置在发起求解的线程（及其深递归搜索线程）上，
+/// 因此超时只停止这一次求解，不影响同一实例上其他线程的求解
+#[derive(Debug, Clone, Copy)]
+struct Deadline {
+    at: Instant,
+    ticks: usize,
+    expired: bool,
+}
+
+thread_local! {
+    static DEADLINE: Cell<Option<Deadline>> = const { Cell::new(None) };
+}
+
+/// 当前线程上的求解是否已超过截止时间；每INTERRUPT_POLL_TICKS次调用才读取一次时钟，超时后保持为true
+fn deadline_expired() -> bool {
+    DEADLINE.with(|cell| {
+        let Some(mut deadline) = cell.get() else {
+            return false;
+        };
+        if !deadline.expired {
+            deadline.ticks += 1;
+            if deadline.ticks.is_multiple_of(INTERRUPT_POLL_TICKS) && Instant::now() >= deadline.at {
+                deadline.expired = true;
             }
+            cell.set(Some(deadline));
         }
+        deadline.expired
+    })
+}
+
+/// 离开作用域（包括panic）时恢复线程原来的截止时间
+struct DeadlineGuard {
+    previous: Option<Deadline>,
+}
+
+impl Drop for DeadlineGuard {
+    fn drop(&mut self) {
+        DEADLINE.with(|cell| cell.set(self.previous));
     }
 }
 
-#[pyclass]
-pub struct SubsetSumSolver {
-    processed_combinations: Arc<AtomicU64>,
-    total_combinations: Arc<AtomicU64>,
-    stop_flag: Arc<AtomicBool>,
-    memory_tracker: MemoryTracker,
-    start_time: Option<Instant>,
+/// 在当前线程上带截止时间执行solve，返回(结果, 搜索是否因超时被停止)
+fn with_deadline<T>(at: Instant, solve: impl FnOnce() -> T) -> (T, bool) {
+    let guard = DeadlineGuard {
+        previous: DEADLINE.with(|cell| cell.replace(Some(Deadline { at, ticks: 0, expired: false }))),
+    };
+    let result = solve();
+    let expired = DEADLINE.with(Cell::get).is_some_and(|deadline| deadline.expired);
+    drop(guard);
+    (result, expired)
 }
 
-#[pymethods]
-impl SubsetSumSolver {
-    #[new]
-    pub fn new() -> Self {
-        SubsetSumSolver {
-            processed_combinations: Arc::new(AtomicU64::new(0)),
-            total_combinations: Arc::new(AtomicU64::new(0)),
-            stop_flag: Arc::new(AtomicBool::new(false)),
-            memory_tracker: MemoryTracker::new(4 * 1024 * 1024 * 1024), // 4GB
-            start_time: None,
+/// 浮点数缩放为整数时支持的最大小数位数
+const MAX_DECIMAL_PLACES: u32 = 10;
+
+/// 位运算路径中计算子集和使用的整数宽度
+///
+/// Auto按输入选择能保证不溢出的最窄宽度；指定的宽度不足以容纳所有子集和时自动加宽
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum IntWidth {
+    Auto,
+    I32,
+    I64,
+    I128,
+}
+
+impl IntWidth {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "auto" => Ok(IntWidth::Auto),
+            "i32" => Ok(IntWidth::I32),
+            "i64" => Ok(IntWidth::I64),
+            "i128" => Ok(IntWidth::I128),
+            _ => Err(SolverError::UnsupportedOption { option: "integer width", value: name.to_string() }.into()),
         }
     }
 
-    #[getter]
-    fn get_progress(&self) -> f64 {
-        let processed = self.processed_combinations.load(Ordering::SeqCst);
-        let total = self.total_combinations.load(Ordering::SeqCst);
-        if total == 0 {
-            return 0.0;
+    fn name(self) -> &'static str {
+        match self {
+            IntWidth::Auto => "auto",
+            IntWidth::I32 => "i32",
+            IntWidth::I64 => "i64",
+            IntWidth::I128 => "i128",
         }
-        processed as f64 / total as f64
     }
 
-    #[getter]
-    fn get_memory_usage(&self) -> usize {
-        self.memory_tracker.get_used_memory()
+    /// 根据输入确定实际使用的宽度（不返回Auto）
+    fn resolve(self, numbers: &[i64], target: i64) -> IntWidth {
+        let required = if fits_i32(numbers, target) {
+            IntWidth::I32
+        } else if fits_i64(numbers) {
+            IntWidth::I64
+        } else {
+            IntWidth::I128
+        };
+        match self {
+            IntWidth::Auto => required,
+            requested => requested.max(required),
+        }
     }
+}
 
-    fn start_timer(&mut self) {
-        self.start_time = Some(Instant::now());
+/// 浮点数缩放为整数时使用的舍入方式
+#[derive(Debug, Clone, Copy)]
+enum RoundingMode {
+    Round,   // 四舍五入（远离零）
+    Floor,   // 向下取整
+    Ceil,    // 向上取整
+    Bankers, // 银行家舍入（四舍六入五成双）
+}
+
+impl RoundingMode {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "round" => Ok(RoundingMode::Round),
+            "floor" => Ok(RoundingMode::Floor),
+            "ceil" => Ok(RoundingMode::Ceil),
+            "bankers" => Ok(RoundingMode::Bankers),
+            _ => Err(SolverError::UnsupportedOption { option: "rounding mode", value: name.to_string() }.into()),
+        }
+    }
+    
+    fn apply(self, value: f64) -> f64 {
+        match self {
+            RoundingMode::Round => value.round(),
+            RoundingMode::Floor => value.floor(),
+            RoundingMode::Ceil => value.ceil(),
+            RoundingMode::Bankers => value.round_ties_even(),
+        }
     }
+}
 
-    fn get_elapsed_time(&self) -> Option<f64> {
-        self.start_time.map(|start| start.elapsed().as_secs_f64())
+/// 检测浮点数的小数位数（按最短十进制表示），最多MAX_DECIMAL_PLACES位
+fn decimal_places(value: f64) -> u32 {
+    let text = format!("{}", value);
+    match text.split_once('.') {
+        Some((_, fraction)) => (fraction.len() as u32).min(MAX_DECIMAL_PLACES),
+        None => 0,
     }
+}
 
-    fn stop_execution(&self) {
-        self.stop_flag.store(true, Ordering::SeqCst);
+/// 将浮点输入和目标值按统一的10的幂缩放为整数，返回(整数列表, 整数目标, 小数位数)
+///
+/// decimals为None时自动检测小数位数（输入和目标值一起检测）；指定时按该位数缩放，输入多出的精度按rounding舍入。
+/// 目标值不做舍入：指定的decimals不足以精确表示目标值时返回错误，否则会求解一个不同的目标。
+/// 缩放结果与最近整数的差在浮点误差范围内时直接取该整数，避免2.9999999被floor成2。
+/// 非零数值缩放后变为0（如超过10位小数的极小值）时返回错误，而不是静默丢弃
+fn scale_to_integers(numbers: &[f64], target: f64, rounding: RoundingMode, decimals: Option<u32>) -> PyResult<(Vec<i64>, i64, u32)> {
+    if numbers.iter().chain(std::iter::once(&target)).any(|x| !x.is_finite()) {
+        return Err(SolverError::NonFiniteInput.into());
     }
+    
+    // 目标值也参与小数位数检测，保证缩放后的目标值精确
+    let decimals = decimals.map(|d| d.min(MAX_DECIMAL_PLACES)).unwrap_or_else(|| {
+        numbers.iter()
+            .chain(std::iter::once(&target))
+            .map(|&x| decimal_places(x))
+            .max()
+            .unwrap_or(0)
+    });
+    if decimal_places(target) > decimals {
+        return Err(SolverError::TargetPrecision { target, decimals }.into());
+    }
+    
+    let scaled_numbers = numbers.iter().map(|&x| scale_value(x, decimals, rounding)).collect::<PyResult<Vec<i64>>>()?;
+    let scaled_target = scale_value(target, decimals, rounding)?;
+    Ok((scaled_numbers, scaled_target, decimals))
+}
 
-    #[pyo3(text_signature = "(numbers, target, max_solutions=10)")]
-    pub fn find_subsets(&self, numbers: Vec<i64>, target: i64, max_solutions: Option<usize>) -> Vec<Vec<usize>> {
-        self.find_subsets_int(&numbers, target, max_solutions.unwrap_or(10))
+/// 把单个有限浮点数按10^decimals缩放为整数
+///
+/// 结果与最近整数的差在浮点误差范围内时直接取该整数，否则按rounding舍入；溢出或非零数值变为0时返回错误
+fn scale_value(x: f64, decimals: u32, rounding: RoundingMode) -> PyResult<i64> {
+    let raw = x * 10f64.powi(decimals as i32);
+    let nearest = raw.round();
+    let scaled = if (raw - nearest).abs() <= 1e-9 * nearest.abs().max(1.0) {
+        nearest
+    } else {
+        rounding.apply(raw)
+    };
+    if scaled.abs() >= i64::MAX as f64 {
+        return Err(SolverError::ScaledOverflow { value: x }.into());
+    }
+    // 非零数值缩放后变为0会被静默地从问题中删除，必须报错
+    if x != 0.0 && scaled == 0.0 {
+        return Err(SolverError::ScaledToZero { value: x, decimals }.into());
     }
+    Ok(scaled as i64)
 }
 
-impl SubsetSumSolver {
-    /// 查找子集，根据问题规模和特征自动选择最合适的算法
-    pub fn find_subsets_int(&self, numbers: &[i64], target: i64, max_solutions: usize) -> Vec<Vec<usize>> {
-        // 重置进度计数器
-        self.processed_combinations.store(0, Ordering::SeqCst);
-        self.total_combinations.store(2u64.pow(numbers.len() as u32), Ordering::SeqCst);
-        self.stop_flag.store(false, Ordering::SeqCst);
-        
-        // 使用问题分析功能选择最佳算法
-        let algorithm = self.analyze_problem(numbers, target);
-        
-        // 根据选择的算法执行相应的求解方法
-        match algorithm {
-            Algorithm::BitManipulation => {
-                self.find_subsets_with_bit(numbers, target, max_solutions)
-            },
-            Algorithm::DynamicProgramming => {
-                self.find_subsets_with_dp(numbers, target, max_solutions)
-            },
-            Algorithm::BacktrackingCompact => {
-                // 创建线程安全的解决方案容器
-                let solutions = Arc::new(Mutex::new(Vec::new()));
-                let should_stop = Arc::clone(&self.stop_flag);
-                
-                // 预处理数据
-                let (sorted_numbers, sorted_indices, prefix_sum) = self.preprocess_data(numbers, target);
-                
-                // 创建当前子集实例
-                let mut current_subset = get_compact_subset_from_pool();
-                
-                // 调用回溯算法的核心实现
-                self.backtracking_with_compact_subset(
-                    &sorted_numbers,
-                    &sorted_indices,
-                    &prefix_sum,
-                    target,
-                    0,
-                    0,
-                    &mut current_subset,
-                    &solutions,
-                    max_solutions,
-                    &should_stop,
-                );
-                
-                // 归还对象到池
-                return_compact_subset_to_pool(current_subset);
-                
-                // 释放锁，获取结果
-                let result = {
-                    let guard = solutions.lock().unwrap();
-                    guard.clone()
-                };
-                
-                result
+/// 把已缩放的容差转换为整数：向下取整，保证不超出指定的偏差；缩放误差范围内的值直接取最近整数
+fn scale_deviation(raw: f64) -> i64 {
+    let nearest = raw.round();
+    if (raw - nearest).abs() <= 1e-9 * nearest.abs().max(1.0) { nearest as i64 } else { raw.floor() as i64 }
+}
+
+/// 与scale_to_integers相同，但逐个读取输入，不需要同时持有全部浮点数
+///
+/// 自动检测小数位数时，遇到位数更多的数就把已缩放的整数乘以10的相应次幂，
+/// 结果与先检测全部位数再缩放相同；整数溢出时返回ScaledOverflow
+fn scale_stream(numbers: impl Iterator<Item = PyResult<f64>>, target: f64, rounding: RoundingMode, decimals: Option<u32>) -> PyResult<(Vec<i64>, i64, u32)> {
+    if !target.is_finite() {
+        return Err(SolverError::NonFiniteInput.into());
+    }
+    let fixed = decimals.map(|d| d.min(MAX_DECIMAL_PLACES));
+    let mut current = fixed.unwrap_or_else(|| decimal_places(target));
+    let mut scaled_numbers = Vec::new();
+    for x in numbers {
+        let x = x?;
+        if !x.is_finite() {
+            return Err(SolverError::NonFiniteInput.into());
+        }
+        let places = decimal_places(x);
+        if fixed.is_none() && places > current {
+            let factor = 10i64.pow(places - current);
+            for scaled in scaled_numbers.iter_mut() {
+                *scaled = i64::checked_mul(*scaled, factor).ok_or_else(|| SolverError::ScaledOverflow {
+                    value: *scaled as f64 / 10f64.powi(current as i32),
+                })?;
             }
+            current = places;
         }
+        scaled_numbers.push(scale_value(x, current, rounding)?);
     }
     
-    /// 分析问题特征，选择最合适的算法
-    fn analyze_problem(&self, numbers: &[i64], target: i64) -> Algorithm {
-        let n = numbers.len();
-        
-        // 基于问题规模的初步判断
-        if n <= 25 {
-            // 小规模问题，适合位运算
-            return Algorithm::BitManipulation;
-        } else if n <= 100 && target <= 10000 {
-            // 中等规模问题，目标和不太大，适合动态规划
-            return Algorithm::DynamicProgramming;
+    if decimal_places(target) > current {
+        return Err(SolverError::TargetPrecision { target, decimals: current }.into());
+    }
+    let scaled_target = scale_value(target, current, rounding)?;
+    Ok((scaled_numbers, scaled_target, current))
+}
+
+/// 计数动态规划：正数numbers中和恰好为target（大于0）的子集个数，按索引计数，超出u64时饱和
+fn count_subsets_saturating(numbers: &[i64], target: usize) -> u64 {
+    let mut count = vec![0u64; target + 1];
+    count[0] = 1;
+    for &num in numbers {
+        let num = num as usize;
+        if num > target {
+            continue;
         }
-        
-        // 对于更大规模问题，进一步分析数据特征
-        
-        // 计算数据统计特征
-        let mut sum = 0i64;
-        let mut min_val = i64::MAX;
-        let mut max_val = i64::MIN;
-        let mut positive_count = 0;
-        
-        for &num in numbers {
-            if num > 0 {
-                sum += num;
-                min_val = min_val.min(num);
-                max_val = max_val.max(num);
-                positive_count += 1;
-            }
+        for sum in (num..=target).rev() {
+            count[sum] = count[sum].saturating_add(count[sum - num]);
         }
-        
-        let _avg = if positive_count > 0 { sum / positive_count } else { 0 };
-        let range = max_val - min_val;
-        
-        // 如果所有数都很小，或者值范围很窄，动态规划可能表现更好
-        if (max_val <= 100 || range <= 100) && target <= 10000 && n <= 200 {
-            return Algorithm::DynamicProgramming;
+    }
+    count[target]
+}
+
+/// 用正态近似估计正数numbers中和为target的子集个数（见estimate_solution_count），在对数空间计算以免溢出
+fn estimate_count_normal(numbers: &[i64], target: i64) -> u64 {
+    let total: i128 = numbers.iter().map(|&x| x as i128).sum();
+    let g = numbers.iter().fold(0i64, |g, &x| {
+        let (mut a, mut b) = (g, x);
+        while b != 0 {
+            (a, b) = (b, a % b);
         }
-        
-        // 如果数字比较大，但数量不太多，也可以使用动态规划
-        if n <= 150 && target <= 50000 {
-            return Algorithm::DynamicProgramming;
+        a
+    });
+    if g == 0 || target as i128 > total || target % g != 0 {
+        return 0;
+    }
+    let mean = total as f64 / 2.0;
+    let variance: f64 = numbers.iter().map(|&x| (x as f64) * (x as f64) / 4.0).sum();
+    let deviation = target as f64 - mean;
+    // log2(2^n * g * exp(-d²/2V) / sqrt(2πV))
+    let log2_count = numbers.len() as f64 + (g as f64).log2()
+        - deviation * deviation / (2.0 * variance) * std::f64::consts::LOG2_E
+        - 0.5 * (2.0 * std::f64::consts::PI * variance).log2();
+    if log2_count >= 64.0 {
+        u64::MAX
+    } else {
+        // 近似值小于1时仍可能有解，至少报告1，避免调度方误以为无解
+        2f64.powf(log2_count).round().max(1.0) as u64
+    }
+}
+
+/// 位集的一段整体左移后按位或：对range内的每个字w（从高到低），bits[w] |= (bits << shift)[w]
+///
+/// shift = word_shift * 64 + bit_shift；从高位字向低位字原地更新，读取的低位字尚未被本轮修改，
+/// 因此每个数只用一次。prefetch_words大于0时提前预取源数据
+fn shift_or_words(bits: &mut [u64], word_shift: usize, bit_shift: u32, range: std::ops::Range<usize>, prefetch_words: usize) {
+    debug_assert!(range.start >= word_shift && range.end <= bits.len());
+    for w in range.rev() {
+        let src = w - word_shift;
+        if prefetch_words > 0 && w % 8 == 0 && src > prefetch_words {
+            prefetch_read(&bits[src - prefetch_words]);
         }
-        
-        // 默认使用内存优化的回溯算法
-        Algorithm::BacktrackingCompact
+        let mut shifted = bits[src] << bit_shift;
+        if bit_shift > 0 && src > 0 {
+            shifted |= bits[src - 1] >> (64 - bit_shift);
+        }
+        bits[w] |= shifted;
     }
-    
-    /// 预处理数据，优化搜索效率
-    fn preprocess_data(&self, numbers: &[i64], target: i64) -> (Vec<i64>, Vec<usize>, Vec<i64>) {
-        // 过滤负数和零，只保留正数
-        let mut filtered: Vec<(usize, i64)> = numbers.iter()
-            .enumerate()
-            .filter(|&(_, &x)| x > 0)
-            .map(|(i, &x)| (i, x))
-            .collect();
+}
 
-        // 按值降序排序，有助于更快找到解
-        filtered.sort_unstable_by(|a, b| b.1.cmp(&a.1));
+/// 将有理数（分子, 分母）通分为整数，返回(缩放后的分子列表, 缩放后的目标, 公分母)
+///
+/// 分母必须为正（fractions.Fraction总是如此）；公分母或缩放后的分子超出i64时返回错误而不是回绕
+fn scale_rationals(numbers: &[(i128, i128)], target: (i128, i128)) -> Result<(Vec<i64>, i64, i64), SolverError> {
+    fn gcd(a: i128, b: i128) -> i128 {
+        if b == 0 { a.abs() } else { gcd(b, a % b) }
+    }
+    let denominator = numbers.iter()
+        .chain(std::iter::once(&target))
+        .try_fold(1i128, |lcm, &(_, den)| (lcm / gcd(lcm, den)).checked_mul(den))
+        .and_then(|lcm| i64::try_from(lcm).ok())
+        .ok_or(SolverError::RationalOverflow)?;
+    let scale = |(num, den): (i128, i128)| -> Result<i64, SolverError> {
+        num.checked_mul(denominator as i128 / den)
+            .and_then(|scaled| i64::try_from(scaled).ok())
+            .ok_or(SolverError::RationalOverflow)
+    };
+    let scaled_numbers = numbers.iter().map(|&x| scale(x)).collect::<Result<Vec<i64>, SolverError>>()?;
+    Ok((scaled_numbers, scale(target)?, denominator))
+}
 
-        // 分离索引和值
-        let sorted_indices: Vec<usize> = filtered.iter().map(|&(i, _)| i).collect();
-        let sorted_numbers: Vec<i64> = filtered.iter().map(|&(_, v)| v).collect();
+/// 每毫秒大约能完成的工作量（组合数或DP格子数），用于时间预算的粗略估计
+const WORK_UNITS_PER_MILLI: u128 = 100_000;
 
-        // 计算前缀和，用于剪枝
-        let prefix_sum = Self::compute_prefix_sum_simd(&sorted_numbers);
+/// 回溯的递归深度超过该值时在独立线程上搜索，线程栈大小由search_stack_size决定
+const DEEP_RECURSION_THRESHOLD: usize = 512;
 
-        (sorted_numbers, sorted_indices, prefix_sum)
-    }
+/// 深递归搜索线程的默认栈大小，足够数万层递归
+const DEFAULT_SEARCH_STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// suggest_memory_limit建议的最小内存限制（MB）
+const MIN_SUGGESTED_MEMORY_MB: usize = 64;
+
+/// 长循环内部每隔多少次迭代检查一次停止标志，保证取消在远低于100ms内生效
+const STOP_CHECK_INTERVAL: usize = 4096;
+
+/// 可中断求解时求解线程检查Python信号（如Ctrl-C）的间隔
+const INTERRUPT_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 停止检查每调用多少次才读取一次时钟，避免热循环中频繁取时间
+const INTERRUPT_POLL_TICKS: usize = 1024;
+
+/// 贪心启发式：按数值降序依次选取不超过目标的数，返回(索引列表, 和)
+///
+/// 结果不保证是最优解，只保证和不超过目标
+fn greedy_closest_under(numbers: &[i64], target: i64) -> (Vec<usize>, i64) {
+    let mut order: Vec<usize> = (0..numbers.len()).filter(|&i| numbers[i] > 0).collect();
+    order.sort_by_key(|&i| (std::cmp::Reverse(numbers[i]), i));
     
-    /// 回溯算法（带紧凑子集表示）
-    fn backtracking_with_compact_subset(
-        &self,
-        numbers: &[i64],
-        indices: &[usize],
-        prefix_sum: &[i64],
-        target: i64,
-        start: usize,
-        current_sum: i64,
-        current_subset: &mut CompactSubset,
-        solutions: &Arc<Mutex<Vec<Vec<usize>>>>,
-        max_solutions: usize,
-        should_stop: &AtomicBool
-    ) {
-        // 检查是否应该停止
-        if should_stop.load(Ordering::SeqCst) {
-            return;
+    let mut chosen = Vec::new();
+    let mut sum = 0;
+    for i in order {
+        if sum + numbers[i] <= target {
+            sum += numbers[i];
+            chosen.push(i);
         }
+    }
+    chosen.sort_unstable();
+    (chosen, sum)
+}
 
-        // 找到一个解
-        if current_sum == target {
-            let mut sols = solutions.lock().unwrap();
-            if sols.len() < max_solutions {
-                // 将紧凑表示转换回索引列表
-                let solution = current_subset.to_indices()
-                    .into_iter()
-                    .map(|idx| indices[idx])
-                    .collect();
-                sols.push(solution);
+/// 加锁并在锁被污染时恢复内部数据
+///
+/// 某次求解panic后锁会被污染，求解器级别的锁（缓存、策略记录等）在之后的每次调用中都会用到，
+/// 这些数据在panic时不会处于不一致的中间状态，直接取回即可，不应让之后的调用全部失败
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// 从panic的载荷中取出消息（panic!的参数通常是&str或String）
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload.downcast_ref::<&str>().map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// FNV-1a 64位哈希，算法固定，不随Rust版本或进程变化
+fn fnv1a_64(bytes: impl IntoIterator<Item = u8>) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes.into_iter().fold(OFFSET_BASIS, |hash, byte| (hash ^ byte as u64).wrapping_mul(PRIME))
+}
+
+/// 调试构建下检查解的索引互不相同且都在范围内，并重新计算解的和断言其落在 [lower, upper] 内，
+/// release构建中不产生任何开销
+///
+/// solution中的索引必须指向numbers（原始输入），用于发现索引映射错误
+#[inline]
+fn debug_assert_solution(numbers: &[i64], solution: &[usize], lower: i64, upper: i64) {
+    debug_assert!(
+        {
+            let mut sorted = solution.to_vec();
+            sorted.sort_unstable();
+            sorted.windows(2).all(|w| w[0] < w[1]) && sorted.last().is_none_or(|&i| i < numbers.len())
+        },
+        "解 {:?} 包含重复或越界的索引（共{}个数字）", solution, numbers.len()
+    );
+    debug_assert!(
+        {
+            let sum: i64 = solution.iter().map(|&i| numbers[i]).sum();
+            sum >= lower && sum <= upper
+        },
+        "解 {:?} 的和不在 [{}, {}] 内", solution, lower, upper
+    );
+}
+
+/// 缓存中的一条求解结果，保存完整输入以排除哈希冲突
+struct CacheEntry {
+    numbers: Vec<i64>,
+    target: i64,
+    max_solutions: usize,
+    solutions: Vec<Vec<usize>>,
+    last_used: u64,
+}
+
+/// 最近求解结果的LRU缓存，按(输入哈希, 目标值)查找
+struct ResultCache {
+    capacity: usize,
+    entries: HashMap<u64, CacheEntry>,
+    tick: u64,
+    hits: u64,
+    misses: u64,
+}
+
+impl ResultCache {
+    fn new(capacity: usize) -> Self {
+        ResultCache {
+            capacity,
+            entries: HashMap::with_capacity(capacity),
+            tick: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+    
+    fn get(&mut self, key: u64, numbers: &[i64], target: i64, max_solutions: usize) -> Option<Vec<Vec<usize>>> {
+        self.tick += 1;
+        match self.entries.get_mut(&key) {
+            Some(entry) if entry.numbers == numbers && entry.target == target && entry.max_solutions == max_solutions => {
+                entry.last_used = self.tick;
+                self.hits += 1;
+                Some(entry.solutions.clone())
+            }
+            _ => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+    
+    fn insert(&mut self, key: u64, numbers: &[i64], target: i64, max_solutions: usize, solutions: &[Vec<usize>]) {
+        if self.capacity == 0 {
+            return;
+        }
+        // 已满时淘汰最久未使用的结果
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&key) {
+            if let Some(&oldest) = self.entries.iter().min_by_key(|(_, e)| e.last_used).map(|(k, _)| k) {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.entries.insert(key, CacheEntry {
+            numbers: numbers.to_vec(),
+            target,
+            max_solutions,
+            solutions: solutions.to_vec(),
+            last_used: self.tick,
+        });
+    }
+}
+
+/// 相同数值合并后的分组：数值及其全部原始索引（升序）
+struct ValueGroup {
+    value: i64,
+    indices: Vec<usize>,
+}
+
+/// 将正数按数值合并为分组，按数值降序排列
+///
+/// 组内按tie_rank从小到大排列（如锚点、偏好高的索引在前），同级按索引升序；tie_rank为空表示无优先
+fn group_duplicates(numbers: &[i64], tie_rank: &[usize]) -> Vec<ValueGroup> {
+    let mut groups: Vec<ValueGroup> = Vec::new();
+    let mut order: Vec<usize> = (0..numbers.len()).filter(|&i| numbers[i] > 0).collect();
+    let rank = |i: usize| tie_rank.get(i).copied().unwrap_or(0);
+    order.sort_by_key(|&i| (std::cmp::Reverse(numbers[i]), rank(i), i));
+    
+    for i in order {
+        match groups.last_mut() {
+            Some(group) if group.value == numbers[i] => group.indices.push(i),
+            _ => groups.push(ValueGroup { value: numbers[i], indices: vec![i] }),
+        }
+    }
+    
+    groups
+}
+
+/// 解的汇总统计，只累计不保存解本身
+#[derive(Default)]
+struct SolutionStats {
+    count: usize,
+    size_histogram: Vec<u64>, // size_histogram[k]为含k个元素的解的数量
+    min_sum: Option<i64>,
+    max_sum: Option<i64>,
+}
+
+impl SolutionStats {
+    fn add(&mut self, len: usize, sum: i64) {
+        self.count += 1;
+        if self.size_histogram.len() <= len {
+            self.size_histogram.resize(len + 1, 0);
+        }
+        self.size_histogram[len] += 1;
+        self.min_sum = Some(self.min_sum.map_or(sum, |min| min.min(sum)));
+        self.max_sum = Some(self.max_sum.map_or(sum, |max| max.max(sum)));
+    }
+}
+
+/// 带得分的解，按得分排序（得分相同时按索引列表）
+struct ScoredSolution {
+    score: f64,
+    solution: Vec<usize>,
+}
+
+impl PartialEq for ScoredSolution {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for ScoredSolution {}
+
+impl PartialOrd for ScoredSolution {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScoredSolution {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.score.total_cmp(&other.score).then_with(|| other.solution.cmp(&self.solution))
+    }
+}
+
+/// 解的评分方式，得分越高越好
+enum Scorer {
+    /// 按原始索引的偏好，multiplicative为true时得分为偏好之积，否则为偏好之和
+    Preference { preferences: Vec<f64>, multiplicative: bool },
+    /// 所选数值的方差越小越好（元素大小越均衡），得分为方差的相反数
+    MinVariance { values: Vec<i64> },
+    /// 所选数值中最小的越大越好，得分为最小值
+    MaxMinElement { values: Vec<i64> },
+    /// 所选数值中最大的越小越好，得分为最大值的相反数
+    MinMaxElement { values: Vec<i64> },
+    /// Python回调 callback(所选数值列表) -> float，调用时持有GIL
+    Callback { values: Vec<i64>, callback: PyObject },
+    /// 所有解得分相同，按索引列表的字典序保留最靠前的
+    IndexOrder,
+    /// 元素越少越好，得分为元素个数的相反数（个数相同时按字典序）
+    FewestElements,
+    /// 按种子对索引列表哈希得到的随机得分，相当于可复现的随机抽样
+    Random { seed: u64 },
+}
+
+impl Scorer {
+    /// 按名称创建内置评分方式："min_variance"、"max_min_element" 或 "min_max_element"
+    fn builtin(name: &str, values: Vec<i64>) -> PyResult<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "min_variance" => Ok(Scorer::MinVariance { values }),
+            "max_min_element" => Ok(Scorer::MaxMinElement { values }),
+            "min_max_element" => Ok(Scorer::MinMaxElement { values }),
+            _ => Err(SolverError::UnsupportedOption { option: "scorer", value: name.to_string() }.into()),
+        }
+    }
+    
+    /// 是否为按解本身选择的方式；这些方式依赖字典序，解需要先按索引升序排列
+    fn is_selection(&self) -> bool {
+        matches!(self, Scorer::IndexOrder | Scorer::FewestElements | Scorer::Random { .. })
+    }
+    
+    /// 计算得分，返回回调抛出的异常
+    fn score(&self, solution: &[usize]) -> PyResult<f64> {
+        let selected = |values: &[i64]| solution.iter().map(|&i| values[i] as f64).collect::<Vec<f64>>();
+        let score = match self {
+            Scorer::Preference { preferences, multiplicative } => {
+                let values = solution.iter().map(|&i| preferences[i]);
+                if *multiplicative { values.product() } else { values.sum() }
+            }
+            Scorer::MinVariance { values } => {
+                let xs = selected(values);
+                let mean = xs.iter().sum::<f64>() / xs.len().max(1) as f64;
+                -xs.iter().map(|x| (x - mean) * (x - mean)).sum::<f64>() / xs.len().max(1) as f64
+            }
+            Scorer::MaxMinElement { values } => selected(values).into_iter().fold(f64::INFINITY, f64::min),
+            Scorer::MinMaxElement { values } => -selected(values).into_iter().fold(f64::NEG_INFINITY, f64::max),
+            Scorer::IndexOrder => 0.0,
+            Scorer::FewestElements => -(solution.len() as f64),
+            Scorer::Random { seed } => {
+                let hash = solution.iter().fold(*seed, |state, &i| SplitMix64::new(state ^ i as u64).next_u64());
+                (hash >> 11) as f64 // 取高53位，转换为f64时不损失精度
+            }
+            Scorer::Callback { values, callback } => {
+                let xs: Vec<i64> = solution.iter().map(|&i| values[i]).collect();
+                return Python::with_gil(|py| callback.call1(py, (xs,))?.extract::<f64>(py));
+            }
+        };
+        Ok(score)
+    }
+}
+
+/// 按得分保留前top_n个解，只保存这些解
+struct RankedSolutions {
+    scorer: Scorer,
+    top_n: usize,
+    best: BinaryHeap<std::cmp::Reverse<ScoredSolution>>, // 小顶堆，堆顶为当前保留的最低分
+    error: Option<PyErr>, // 评分回调抛出的第一个异常，由into_sorted返回
+}
+
+impl RankedSolutions {
+    fn new(scorer: Scorer, top_n: usize) -> Self {
+        RankedSolutions { scorer, top_n, best: BinaryHeap::new(), error: None }
+    }
+    
+    /// 评分并按需保留，评分回调出错时保存异常并返回false，调用者应停止搜索
+    fn add(&mut self, mut solution: Vec<usize>) -> bool {
+        if self.top_n == 0 {
+            return true;
+        }
+        if self.scorer.is_selection() {
+            solution.sort_unstable();
+        }
+        let score = match self.scorer.score(&solution) {
+            Ok(score) => score,
+            Err(err) => {
+                self.error.get_or_insert(err);
+                return false;
+            }
+        };
+        let candidate = ScoredSolution { score, solution };
+        if self.best.len() < self.top_n {
+            self.best.push(std::cmp::Reverse(candidate));
+        } else if self.best.peek().is_some_and(|lowest| candidate > lowest.0) {
+            self.best.pop();
+            self.best.push(std::cmp::Reverse(candidate));
+        }
+        true
+    }
+    
+    /// 按得分从高到低返回保留的解；评分回调出错时返回该异常
+    fn into_sorted(self) -> PyResult<Vec<(Vec<usize>, f64)>> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        let mut best: Vec<ScoredSolution> = self.best.into_iter().map(|entry| entry.0).collect();
+        best.sort_by(|a, b| b.cmp(a));
+        Ok(best.into_iter().map(|entry| (entry.solution, entry.score)).collect())
+    }
+}
+
+/// 和不等于target的解中保留离target最近的limit个（近似匹配建议），只保存这些解
+struct NearMisses {
+    target: i64,
+    limit: usize,
+    closest: BinaryHeap<(u64, i64, Vec<usize>)>, // (与target的距离, 和, 索引列表)的大顶堆，堆顶为当前保留的最远者
+}
+
+impl NearMisses {
+    fn new(target: i64, limit: usize) -> Self {
+        NearMisses { target, limit, closest: BinaryHeap::new() }
+    }
+    
+    fn add(&mut self, solution: Vec<usize>, sum: i64) {
+        let candidate = (sum.abs_diff(self.target), sum, solution);
+        if self.closest.len() < self.limit {
+            self.closest.push(candidate);
+        } else if self.closest.peek().is_some_and(|farthest| candidate < *farthest) {
+            self.closest.pop();
+            self.closest.push(candidate);
+        }
+    }
+    
+    /// 按距离从近到远返回 [(索引列表, 和), ...]，距离相同时和较小的在前
+    fn into_sorted(self) -> Vec<(Vec<usize>, i64)> {
+        self.closest.into_sorted_vec().into_iter().map(|(_, sum, solution)| (solution, sum)).collect()
+    }
+}
+
+/// find_subsets_with_near_misses的结果：(精确解列表, [(近似解索引列表, 和), ...])
+type ExactAndNearMisses = (Vec<Vec<usize>>, Vec<(Vec<usize>, i64)>);
+
+/// find_subsets_with_unused的一个结果：(索引列表, 未使用个数, 未使用索引或None)
+type UnusedSolution = (Vec<usize>, usize, Option<Vec<usize>>);
+
+/// 搜索过程中收集的解
+///
+/// 回溯搜索是单线程的（深递归时也只是换到一个栈更大的线程上），收集器外层的Mutex没有竞争，
+/// 每个解只加锁一次；解的分配与内存登记才是主要开销，因此不需要无锁队列或按线程分片
+#[derive(Default)]
+struct SolutionCollector {
+    solutions: Vec<(Vec<usize>, i64)>,
+    flushed: usize,       // 已通过回调交给调用者的解数量
+    tracked_bytes: usize, // 当前持有的解在内存追踪器中登记的字节数
+    stats: Option<SolutionStats>, // 设置时只累计统计，不保存解
+    frequency: Option<Vec<u64>>, // 设置时按原始索引累计每个元素出现在多少个解中（与stats一起使用）
+    seen_sums: Option<HashSet<i64>>, // 设置时每个不同的和只记录第一个解
+    ranked: Option<RankedSolutions>, // 设置时只按偏好得分保留最好的若干个解
+    near_misses: Option<NearMisses>, // 设置时和不等于目标的解只进入这里，不计入解的数量
+    sink: Option<SolutionSink>, // 设置时把解逐个送入有界通道，不在收集器中保存
+}
+
+/// 分页搜索的输出端：先跳过skip个解，之后的解送入有界通道，通道满时搜索阻塞等待
+struct SolutionSink {
+    sender: SyncSender<Vec<usize>>,
+    skip: usize,
+}
+
+impl SolutionCollector {
+    /// 已记录的解总数（包括已交给回调的和只计入统计的）
+    fn total(&self) -> usize {
+        self.flushed + self.solutions.len() + self.stats.as_ref().map_or(0, |stats| stats.count)
+    }
+}
+
+/// 一个解在内存中大约占用的字节数
+fn solution_bytes(solution: &[usize]) -> usize {
+    std::mem::size_of::<(Vec<usize>, i64)>() + std::mem::size_of_val(solution)
+}
+
+/// 单次回溯搜索的参数
+#[derive(Clone, Copy)]
+struct SearchRequest<'a> {
+    lower: i64,
+    upper: i64,
+    max_solutions: usize, // 只作为上限，不要按它预分配容量
+    expected_solutions: usize, // 调用者给出的预计解数量，按它（不超过max_solutions）预分配解的容器；0表示不预分配
+    minimal_only: bool,
+    anchors: &'a [bool], // 按原始索引标记锚点，解至少包含一个锚点；为空表示不限制
+    distinct_sums: bool, // 每个不同的和只记录第一个解
+    canonical: bool, // 按(数值, 个数)分组搜索，与set_merge_duplicates效果相同但只作用于本次搜索
+    preferences: &'a [f64], // 按原始索引的偏好，相同数值中偏好高的优先被选中；为空表示无偏好
+    windows: &'a [(i64, i64)], // 按原始索引的有效时间窗（闭区间），解中所有时间窗须有公共时刻；为空表示不限制
+    fifo: bool, // 按原始索引升序搜索，先找到使用靠前元素的解
+    divisible_by: Option<i64>, // 解的和须是它的倍数（必须为正）；None表示不限制
+    all_positions: bool, // 不跳过、不合并相同数值，数值相同但索引不同的组合都是独立的解
+}
+
+impl<'a> SearchRequest<'a> {
+    /// 查找和落在 [lower, upper] 内的子集，无其他约束
+    fn new(lower: i64, upper: i64, max_solutions: usize) -> Self {
+        SearchRequest {
+            lower,
+            upper,
+            max_solutions,
+            expected_solutions: 0,
+            minimal_only: false,
+            anchors: &[],
+            distinct_sums: false,
+            canonical: false,
+            preferences: &[],
+            windows: &[],
+            fifo: false,
+            divisible_by: None,
+            all_positions: false,
+        }
+    }
+    
+    /// 相同数值之间的先后等级（越小越靠前）：锚点优先，其次按偏好从高到低，
+    /// 设置时间窗时按时间窗排列（使数值和时间窗都相同的元素相邻），都未设置时为空
+    fn tie_ranks(&self) -> Vec<usize> {
+        if !self.anchors.is_empty() {
+            return self.anchors.iter().map(|&anchor| !anchor as usize).collect();
+        }
+        let mut order: Vec<usize>;
+        if !self.preferences.is_empty() {
+            order = (0..self.preferences.len()).collect();
+            order.sort_by(|&a, &b| self.preferences[b].total_cmp(&self.preferences[a]));
+        } else {
+            order = (0..self.windows.len()).collect();
+            order.sort_by_key(|&i| self.windows[i]);
+        }
+        let mut ranks = vec![0; order.len()];
+        for (rank, i) in order.into_iter().enumerate() {
+            ranks[i] = rank;
+        }
+        ranks
+    }
+}
+
+/// 统计标记的后缀和：suffix[i]为flags[i..]中true的个数，flags为空时返回空
+fn suffix_counts(flags: &[bool]) -> Vec<usize> {
+    if flags.is_empty() {
+        return Vec::new();
+    }
+    let mut suffix = vec![0; flags.len() + 1];
+    for i in (0..flags.len()).rev() {
+        suffix[i] = suffix[i + 1] + flags[i] as usize;
+    }
+    suffix
+}
+
+/// 回溯搜索中的剪枝规则
+#[derive(Clone, Copy)]
+enum PruneRule {
+    OverTarget, // 当前和（或选入下一个数后的和）超过上限
+    EndOfList,  // 剩余元素个数不足以满足下限，或已达到元素个数上限
+    SumBound,   // 剩余数字全选上也达不到下限
+}
+
+/// 各剪枝规则剪掉的分支数，只在启用统计时累计
+#[derive(Default)]
+struct PruneStats {
+    over_target: AtomicU64,
+    end_of_list: AtomicU64,
+    sum_bound: AtomicU64,
+}
+
+impl PruneStats {
+    fn record(&self, rule: PruneRule) {
+        let counter = match rule {
+            PruneRule::OverTarget => &self.over_target,
+            PruneRule::EndOfList => &self.end_of_list,
+            PruneRule::SumBound => &self.sum_bound,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+    
+    fn reset(&self) {
+        for counter in [&self.over_target, &self.end_of_list, &self.sum_bound] {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+    
+    fn snapshot(&self) -> (u64, u64, u64) {
+        (
+            self.over_target.load(Ordering::Relaxed),
+            self.end_of_list.load(Ordering::Relaxed),
+            self.sum_bound.load(Ordering::Relaxed),
+        )
+    }
+}
+
+/// 可中断求解期间在求解线程上协作式检查Python信号（Ctrl-C），不另起线程
+///
+/// 只在run_interruptible期间启用。各算法检查停止标志时调用poll，按调用次数和时间节流，
+/// 每隔INTERRUPT_CHECK_INTERVAL短暂获取GIL调用一次check_signals；收到信号异常后保存第一个异常，
+/// 之后的poll都返回true。非主线程上check_signals总是成功，等同于不可中断
+#[derive(Default)]
+struct InterruptPoll {
+    active: AtomicUsize, // 正在进行的可中断求解数（同一实例可能被多个线程同时使用）
+    raised: AtomicBool,
+    ticks: AtomicUsize,
+    last_check: Mutex<Option<Instant>>,
+    error: Mutex<Option<PyErr>>,
+}
+
+impl InterruptPoll {
+    /// 开始一次可中断求解；没有其他进行中的求解时清除上次的中断
+    fn start(&self) {
+        let mut error = lock(&self.error);
+        if self.active.fetch_add(1, Ordering::SeqCst) == 0 {
+            *error = None;
+            self.raised.store(false, Ordering::SeqCst);
+            *lock(&self.last_check) = None;
+        }
+    }
+    
+    /// 结束一次可中断求解，返回期间收到的信号异常
+    fn finish(&self, py: Python) -> Option<PyErr> {
+        let error = lock(&self.error);
+        self.active.fetch_sub(1, Ordering::SeqCst);
+        error.as_ref().map(|err| err.clone_ref(py))
+    }
+    
+    /// 节流的信号检查，返回是否已收到中断；未启用时不做任何事
+    #[inline]
+    fn poll(&self) -> bool {
+        if self.active.load(Ordering::Relaxed) == 0 {
+            return false;
+        }
+        if self.raised.load(Ordering::Relaxed) {
+            return true;
+        }
+        if !self.ticks.fetch_add(1, Ordering::Relaxed).is_multiple_of(INTERRUPT_POLL_TICKS) {
+            return false;
+        }
+        self.check_now()
+    }
+    
+    /// 距上次检查已超过INTERRUPT_CHECK_INTERVAL时获取GIL检查信号，返回是否已收到中断
+    fn check_now(&self) -> bool {
+        if self.active.load(Ordering::SeqCst) == 0 {
+            return false;
+        }
+        {
+            let mut last_check = lock(&self.last_check);
+            let now = Instant::now();
+            if last_check.is_some_and(|last| now.duration_since(last) < INTERRUPT_CHECK_INTERVAL) {
+                return self.raised.load(Ordering::SeqCst);
+            }
+            *last_check = Some(now);
+        }
+        if let Err(err) = Python::with_gil(|py| py.check_signals()) {
+            lock(&self.error).get_or_insert(err);
+            self.raised.store(true, Ordering::SeqCst);
+        }
+        self.raised.load(Ordering::SeqCst)
+    }
+}
+
+/// 回溯搜索的共享上下文，避免在递归中传递大量参数
+///
+/// 合并重复值时，numbers为各分组的数值，prefix_sum为各分组全部选中时的前缀和
+struct SearchContext<'a> {
+    source: &'a [i64],
+    numbers: &'a [i64],
+    indices: &'a [usize],
+    groups: &'a [ValueGroup],
+    prefix_sum: &'a [i64],
+    lower: i64,
+    upper: i64,
+    minimal_only: bool,
+    min_len: usize,
+    max_len: usize,
+    allow_empty: bool,
+    allow_full: bool,
+    anchor_suffix: &'a [usize], // 从每个位置（或分组）起剩余的锚点数，为空表示不限制
+    windows: &'a [(i64, i64)], // 按排序后位置的有效时间窗，为空表示不限制
+    descending: bool, // numbers是否按数值降序排列（FIFO模式下按原始索引排列）
+    all_positions: bool, // 不跳过相同数值，每个索引组合都单独搜索
+    divisor: i64, // 解的和须是它的倍数，不限制时为1；lower和upper已收紧到它的倍数
+    prune_stats: Option<&'a PruneStats>, // 启用剪枝统计时记录每次剪枝
+    max_depth: &'a AtomicUsize, // 本次搜索到达的最大递归深度
+    solutions: &'a Mutex<SolutionCollector>,
+    max_solutions: usize,
+    should_stop: &'a AtomicBool,
+    truncated: &'a AtomicBool, // 超出内存限额或把解交给了解回调时设置
+    memory: &'a MemoryTracker,
+    solution_callback: Option<&'a PyObject>,
+    callback_error: &'a Mutex<Option<PyErr>>,
+}
+
+impl SearchContext<'_> {
+    /// 记录一次剪枝，未启用统计时不做任何事
+    #[inline]
+    fn pruned(&self, rule: PruneRule) {
+        if let Some(stats) = self.prune_stats {
+            stats.record(rule);
+        }
+    }
+    
+    /// 更新到达的最大递归深度；先读后写，深度未刷新纪录时不产生写操作
+    #[inline]
+    fn reached(&self, depth: usize) {
+        if depth > self.max_depth.load(Ordering::Relaxed) {
+            self.max_depth.fetch_max(depth, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 子集和求解器
+///
+/// 并发模型：求解方法只借用&self并在计算期间释放GIL。实例之间不共享求解状态（CompactSubset对象池是线程局部的，
+/// 也不使用任何线程池），因此不同实例可以在不同线程上完全并行。唯一的全局状态是错误消息语言
+/// （MESSAGE_LANGUAGE，由模块函数set_language设置），它对整个进程生效，只影响错误消息的文字，不影响求解结果。
+/// 同一实例上的并发求解共享进度、停止标志和内存计数；求解进行时调用set_*等需要&mut self的方法
+/// 会因实例已被借用而抛出RuntimeError
+#[pyclass]
+pub struct SubsetSumSolver {
+    processed_combinations: Arc<AtomicU64>,
+    total_combinations: Arc<AtomicU64>,
+    stop_flag: Arc<AtomicBool>,
+    memory_tracker: MemoryTracker,
+    start_time: Option<Instant>,
+    seed: Option<u64>,
+    min_len: usize,
+    max_len: Option<usize>,
+    merge_duplicates: bool,
+    thresholds: StrategyThresholds,
+    int_width: IntWidth,
+    search_stack_size: usize, // 深递归搜索线程的栈大小（字节）
+    solve_started: Mutex<Option<Instant>>, // 最近一次求解开始的时间，用于计算吞吐量
+    last_strategy: Mutex<&'static str>, // 最近一次求解实际使用的策略
+    solution_callback: Option<PyObject>,
+    callback_error: Mutex<Option<PyErr>>, // 解回调抛出的第一个异常，由求解入口取出后抛出
+    improvement_callback: Option<PyObject>, // 优化类求解找到更优解时调用
+    collect_prune_stats: bool,
+    prune_stats: PruneStats, // 最近一次求解中回溯各剪枝规则的命中次数
+    max_depth: AtomicUsize, // 最近一次回溯搜索到达的最大递归深度
+    allow_empty: bool,
+    allow_full: bool,
+    cache: Mutex<Option<ResultCache>>,
+    cancelled: AtomicBool,
+    truncated: AtomicBool, // 最近一次求解因内存限额提前停止，或部分解已交给解回调，返回的结果不完整
+    partial_on_interrupt: bool, // 被KeyboardInterrupt中断时返回已找到的解，而不是抛出异常
+    interrupt: InterruptPoll, // 可中断求解期间的信号检查
+    dp_block_words: usize, // 位集动态规划每个超级块的字数，0表示整张表为一块
+    dp_prefetch_words: usize, // 位集动态规划提前预取的字数，0表示不预取
+}
+
+// 编译期确认求解器可以跨线程共享，释放GIL后的并发求解依赖于此
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SubsetSumSolver>();
+};
+
+impl Default for SubsetSumSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[pymethods]
+impl SubsetSumSolver {
+    #[new]
+    pub fn new() -> Self {
+        SubsetSumSolver {
+            processed_combinations: Arc::new(AtomicU64::new(0)),
+            total_combinations: Arc::new(AtomicU64::new(0)),
+            stop_flag: Arc::new(AtomicBool::new(false)),
+            memory_tracker: MemoryTracker::new(4 * 1024 * 1024 * 1024), // 4GB
+            start_time: None,
+            seed: None,
+            min_len: 0,
+            max_len: None,
+            merge_duplicates: false,
+            thresholds: StrategyThresholds::default(),
+            int_width: IntWidth::Auto,
+            search_stack_size: DEFAULT_SEARCH_STACK_SIZE,
+            solve_started: Mutex::new(None),
+            last_strategy: Mutex::new("none"),
+            solution_callback: None,
+            callback_error: Mutex::new(None),
+            improvement_callback: None,
+            collect_prune_stats: false,
+            prune_stats: PruneStats::default(),
+            max_depth: AtomicUsize::new(0),
+            allow_empty: false,
+            allow_full: true,
+            cache: Mutex::new(None),
+            cancelled: AtomicBool::new(false),
+            truncated: AtomicBool::new(false),
+            partial_on_interrupt: false,
+            interrupt: InterruptPoll::default(),
+            dp_block_words: 0,
+            dp_prefetch_words: 0,
+        }
+    }
+
+    #[getter]
+    fn get_progress(&self) -> f64 {
+        let processed = self.processed_combinations.load(Ordering::SeqCst);
+        let total = self.total_combinations.load(Ordering::SeqCst);
+        if total == 0 {
+            return 0.0;
+        }
+        processed as f64 / total as f64
+    }
+
+    /// 最近一次求解实际使用的策略
+    ///
+    /// "bitmask"、"dp"、"backtracking"；单元素预扫描已给出全部解时为 "single_element"，
+    /// 命中结果缓存时为 "cache"，连续子数组搜索为 "contiguous"，分桶分配由贪心完成时为 "greedy"，
+    /// 输入全为非负数而目标为负、无需搜索时为 "infeasible"，超出位运算规模的含负数输入为 "signed_backtracking"，尚未求解时为 "none"。
+    /// 区间、锚点等只用回溯的接口记录为 "backtracking"
+    fn get_last_strategy(&self) -> &'static str {
+        *lock(&self.last_strategy)
+    }
+
+    /// 最近一次求解的吞吐量：已处理的组合（或DP元素、搜索节点）数 / 求解开始后经过的秒数
+    ///
+    /// 求解过程中可随时查询，吞吐量持续下降通常说明搜索陷入停滞；尚未求解时返回0
+    fn get_throughput(&self) -> f64 {
+        let started = *lock(&self.solve_started);
+        let elapsed = started.map_or(0.0, |start| start.elapsed().as_secs_f64());
+        if elapsed <= 0.0 {
+            return 0.0;
+        }
+        self.processed_combinations.load(Ordering::SeqCst) as f64 / elapsed
+    }
+
+    #[getter]
+    fn get_memory_usage(&self) -> usize {
+        self.memory_tracker.get_used_memory()
+    }
+
+    /// 已用内存的历史最大值（字节），不会超过设置峰值时的内存限制
+    #[getter]
+    fn get_peak_memory_usage(&self) -> usize {
+        self.memory_tracker.peak_memory.load(self.memory_tracker.load_ordering()) as usize
+    }
+
+    /// 设置内存限制（MB）
+    fn set_memory_limit(&mut self, limit_mb: usize) {
+        self.memory_tracker.max_memory = limit_mb.saturating_mul(1024 * 1024);
+    }
+
+    /// 根据输入规模和将选用的算法估算合适的内存限制（MB），可直接传给set_memory_limit
+    ///
+    /// 动态规划按 target+1 个格子估算，位运算与回溯按最多max_solutions个解估算，
+    /// 结果留出一倍余量且不低于MIN_SUGGESTED_MEMORY_MB
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10)")]
+    fn suggest_memory_limit(&self, numbers: Vec<i64>, target: i64, max_solutions: Option<usize>) -> usize {
+        let bytes = self.estimate_memory_bytes(&numbers, target, max_solutions.unwrap_or(10));
+        let mb = bytes.saturating_mul(2).div_ceil(1024 * 1024);
+        mb.clamp(MIN_SUGGESTED_MEMORY_MB, usize::MAX / (1024 * 1024))
+    }
+
+    /// 设置解回调，传入None取消
+    ///
+    /// 回溯搜索中已收集的解超出内存限制时，会以 callback([(索引列表, 和), ...]) 的形式
+    /// 分批交给回调并清空后继续搜索，因此解是分块到达的：搜索过程中的各批通过回调送出，
+    /// 最后剩余的一批作为返回值。回调抛出异常时停止搜索，求解方法随后抛出该异常
+    fn set_solution_callback(&mut self, callback: Option<PyObject>) {
+        self.solution_callback = callback;
+    }
+
+    /// 设置更优解回调，传入None取消
+    ///
+    /// find_balanced_partition在搜索过程中每找到差值更小的划分，就以 callback(A组索引, 差值)
+    /// 报告当前最优解，可用于逐步显示结果；被停止时最后一次回调的结果就是目前为止的最优解。
+    /// 回调在持有GIL时调用；回调抛出异常时停止搜索，find_balanced_partition随后抛出该异常
+    fn set_improvement_callback(&mut self, callback: Option<PyObject>) {
+        self.improvement_callback = callback;
+    }
+
+    /// 设置内存追踪计数器的内存序："relaxed"（默认）、"acqrel" 或 "seqcst"
+    fn set_memory_ordering(&mut self, ordering: &str) -> PyResult<()> {
+        self.memory_tracker.ordering = match ordering.to_ascii_lowercase().as_str() {
+            "relaxed" => Ordering::Relaxed,
+            "acqrel" => Ordering::AcqRel,
+            "seqcst" => Ordering::SeqCst,
+            _ => return Err(SolverError::UnsupportedOption { option: "memory ordering", value: ordering.to_string() }.into()),
+        };
+        Ok(())
+    }
+
+    /// 设置深递归搜索线程的栈大小（MB），默认256MB
+    ///
+    /// 回溯搜索的递归深度可达元素个数，超过DEEP_RECURSION_THRESHOLD层时会在一个使用该栈大小的
+    /// 独立线程上搜索，避免在默认线程栈上溢出。栈只按需占用物理内存
+    fn set_stack_size(&mut self, stack_mb: usize) -> PyResult<()> {
+        if stack_mb == 0 {
+            return Err(SolverError::InvalidStackSize.into());
+        }
+        self.search_stack_size = stack_mb.saturating_mul(1024 * 1024);
+        Ok(())
+    }
+
+    /// 获取当前构建和CPU上实际使用的SIMD实现："avx2" 或 "scalar"
+    ///
+    /// 以no_simd特性构建（或非x86_64目标、CPU不支持AVX2）时为 "scalar"
+    fn get_simd_support(&self) -> &'static str {
+        detect_simd_support()
+    }
+
+    /// 内置自检：用cases组小规模随机输入比较各策略与暴力枚举的结果，返回 (是否通过, 第一个不一致的用例)
+    ///
+    /// 检查位运算（i32/i64/i128三种宽度，i32在支持时走AVX2）、回溯（逐个与合并重复值两种方式）、
+    /// 动态规划与位集动态规划，以及AVX2与标量掩码求和，用于确认当前构建在本机硬件上的正确性。
+    /// 使用默认设置的独立求解器，不受本实例的设置影响；seed相同时用例相同
+    #[pyo3(text_signature = "(cases=200, seed=None)")]
+    fn self_test(&self, py: Python, cases: Option<usize>, seed: Option<u64>) -> (bool, Option<String>) {
+        let mut rng = SplitMix64::new(seed.or(self.seed).unwrap_or(0x5EED));
+        let cases = cases.unwrap_or(200);
+        let failure = py.allow_threads(|| {
+            (0..cases).find_map(|_| Self::self_test_case(&mut rng).err())
+        });
+        (failure.is_none(), failure)
+    }
+
+    /// 设置位运算路径计算子集和的整数宽度："auto"（默认）、"i32"、"i64" 或 "i128"
+    ///
+    /// - i32：最快，AVX2每次处理8个数，要求所有数绝对值之和与目标不超过约21亿
+    /// - i64：通用宽度，要求所有数绝对值之和不超过约9.2e18
+    /// - i128：任意i64输入都不会溢出，约慢一倍，适合缩放后极大的数值
+    ///
+    /// auto按输入选择不溢出的最窄宽度；指定的宽度容纳不下时自动加宽，不会产生错误结果。
+    /// 动态规划与回溯按i64计算，不受此设置影响
+    fn set_integer_width(&mut self, width: &str) -> PyResult<()> {
+        self.int_width = IntWidth::parse(width)?;
+        Ok(())
+    }
+
+    /// 获取位运算路径的整数宽度设置
+    fn get_integer_width(&self) -> &'static str {
+        self.int_width.name()
+    }
+
+    /// 获取内存追踪计数器当前使用的内存序
+    fn get_memory_ordering(&self) -> &'static str {
+        match self.memory_tracker.ordering {
+            Ordering::SeqCst => "seqcst",
+            Ordering::AcqRel => "acqrel",
+            _ => "relaxed",
+        }
+    }
+
+    fn start_timer(&mut self) {
+        self.start_time = Some(Instant::now());
+    }
+
+    fn get_elapsed_time(&self) -> Option<f64> {
+        self.start_time.map(|start| start.elapsed().as_secs_f64())
+    }
+
+    fn stop_execution(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        self.stop_flag.store(true, Ordering::SeqCst);
+    }
+
+    /// 设置被Ctrl-C中断时的行为：False（默认）抛出KeyboardInterrupt，True返回中断前已找到的解
+    ///
+    /// 只对find_subsets、find_subsets_float（含find_subsets_float_iter）、find_subsets_numeric、find_subsets_mask_bytes、solve_within和find_subsets_in_range系列生效，
+    /// 这些方法在求解期间每隔约50ms检查一次信号，中断后停止搜索的方式与stop_execution相同
+    fn set_partial_on_interrupt(&mut self, enabled: bool) {
+        self.partial_on_interrupt = enabled;
+    }
+
+    /// 启用最近求解结果的LRU缓存，capacity为最多缓存的结果数，0表示关闭缓存
+    ///
+    /// 缓存键包含输入、目标值、最大解数量、内存限额和所有影响结果的求解器设置；
+    /// 被stop_execution中断、因内存限额提前停止或部分解已交给解回调的结果不完整，不会缓存
+    fn enable_cache(&self, capacity: usize) {
+        *lock(&self.cache) = (capacity > 0).then(|| ResultCache::new(capacity));
+    }
+
+    /// 获取缓存统计 (命中次数, 未命中次数, 当前缓存条数)，未启用缓存时全为0
+    fn get_cache_stats(&self) -> (u64, u64, usize) {
+        match lock(&self.cache).as_ref() {
+            Some(cache) => (cache.hits, cache.misses, cache.entries.len()),
+            None => (0, 0, 0),
+        }
+    }
+
+    /// 设置随机种子，使所有带随机性的功能可复现
+    ///
+    /// 当前使用种子的功能：
+    /// - 回溯算法预处理：相同数值元素之间的排序（决定重复值中报告哪些索引）
+    /// - find_subsets_selected的"random"抽样
+    ///
+    /// 未设置种子时，相同数值按原始索引顺序排列
+    fn set_seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+
+    /// 启用或关闭回溯剪枝统计，默认关闭；统计每次剪枝有少量开销，只建议调优时开启
+    fn set_prune_stats(&mut self, enabled: bool) {
+        self.collect_prune_stats = enabled;
+    }
+
+    /// 最近一次求解中回溯各剪枝规则剪掉的分支数：(超过上限, 元素个数不足或已达上限, 剩余和达不到下限)
+    ///
+    /// 只统计回溯算法（位运算与动态规划没有剪枝），未启用统计时全为0；可在求解过程中查询
+    fn get_prune_stats(&self) -> (u64, u64, u64) {
+        self.prune_stats.snapshot()
+    }
+
+    /// 最近一次回溯搜索到达的最大递归深度：逐个搜索时为子集的最大元素个数，合并重复值时为分组层数
+    ///
+    /// 与剪枝统计、吞吐量一起用于分析搜索为何缓慢；不需要开启，可在求解过程中查询，位运算与动态规划为0
+    fn get_max_depth(&self) -> usize {
+        self.max_depth.load(Ordering::Relaxed)
+    }
+
+    /// 获取当前随机种子，未设置时返回None
+    fn get_seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// 限制解中元素个数的范围 [min_len, max_len]，max_len为None表示不限上限
+    ///
+    /// 设置了个数限制时动态规划无法保证约束，会改用回溯算法
+    #[pyo3(text_signature = "(min_len=0, max_len=None)")]
+    fn set_size_limits(&mut self, min_len: Option<usize>, max_len: Option<usize>) {
+        self.min_len = min_len.unwrap_or(0);
+        self.max_len = max_len;
+    }
+
+    /// 设置是否接受空集和全集作为解，对所有算法一致生效
+    ///
+    /// 默认不接受空集（和为0的平凡解），接受全集（包含所有输入元素的子集）。
+    /// 该限制同样作用于位运算和动态规划返回的近似组合
+    #[pyo3(text_signature = "(allow_empty=False, allow_full=True)")]
+    fn set_subset_bounds(&mut self, allow_empty: Option<bool>, allow_full: Option<bool>) {
+        self.allow_empty = allow_empty.unwrap_or(false);
+        self.allow_full = allow_full.unwrap_or(true);
+    }
+
+    /// 设置算法选择的分界点，默认 (25, 100, 10000)
+    ///
+    /// - bitmask_max：元素个数不超过该值时使用位运算（最大32）
+    /// - dp_max_n、dp_max_target：元素个数和目标值都不超过这两个值时使用动态规划
+    ///
+    /// 更大规模问题的数据特征判断（数值范围窄、n不超过150等）不受这些参数影响
+    fn set_strategy_thresholds(&mut self, bitmask_max: usize, dp_max_n: usize, dp_max_target: i64) -> PyResult<()> {
+        if bitmask_max > MAX_BITMASK_N {
+            return Err(SolverError::BitmaskThresholdTooLarge { max: MAX_BITMASK_N }.into());
+        }
+        self.thresholds = StrategyThresholds {
+            bitmask_max_n: bitmask_max,
+            dp_max_n,
+            dp_max_target,
+        };
+        Ok(())
+    }
+
+    /// 设置位集动态规划（is_target_reachable）更新的分块方式，默认 (0, 0) 与不分块完全相同
+    ///
+    /// - block_words：每个数的整体移位按block_words个u64字为一个超级块从高位向低位处理，
+    ///   每块之间检查一次停止标志；0表示整张表为一块
+    /// - prefetch_distance：处理每个缓存行时预取前方（更低地址）prefetch_distance个字处的源数据；0表示不预取
+    ///
+    /// 只影响内存访问顺序和停止的响应速度，结果与默认设置逐位相同。目标极大、表远超缓存时可调优
+    #[pyo3(text_signature = "(block_words=0, prefetch_distance=0)")]
+    fn set_dp_blocking(&mut self, block_words: Option<usize>, prefetch_distance: Option<usize>) {
+        self.dp_block_words = block_words.unwrap_or(0);
+        self.dp_prefetch_words = prefetch_distance.unwrap_or(0);
+    }
+
+    /// 获取当前的算法选择分界点 (bitmask_max, dp_max_n, dp_max_target)
+    fn get_strategy_thresholds(&self) -> (usize, usize, i64) {
+        let t = self.thresholds;
+        (t.bitmask_max_n, t.dp_max_n, t.dp_max_target)
+    }
+
+    /// 合并相同数值为(数值, 个数)分组，按每组选取的个数搜索
+    ///
+    /// 启用后总是使用回溯算法，数值相同的组合只返回一次，每组选k个时取该组最靠前的k个原始索引
+    fn set_merge_duplicates(&mut self, enabled: bool) {
+        self.merge_duplicates = enabled;
+    }
+
+    /// 单独执行合并重复值的预处理：返回 ([(数值, [原始索引, ...]), ...], 被合并掉的元素个数)
+    ///
+    /// 分组与set_merge_duplicates启用后回溯实际使用的完全相同：只包含正数，按数值降序，组内索引升序。
+    /// 被合并掉的元素个数 = 参与分组的元素个数 - 分组数，可用于判断启用合并是否值得
+    #[staticmethod]
+    #[pyo3(text_signature = "(numbers)")]
+    fn collapse_duplicates(numbers: Vec<i64>) -> (Vec<(i64, Vec<usize>)>, usize) {
+        let groups = group_duplicates(&numbers, &[]);
+        let elements: usize = groups.iter().map(|g| g.indices.len()).sum();
+        let collapsed = elements - groups.len();
+        (groups.into_iter().map(|g| (g.value, g.indices)).collect(), collapsed)
+    }
+
+    /// 求解整数子集和，返回解的原始索引列表
+    ///
+    /// max_solutions只是上限，各算法都不按它预先分配空间，解的容器随找到的解增长，
+    /// 因此可以传入极大的值（如 sys.maxsize）表示不限制数量
+    ///
+    /// expected_solutions是预计的解数量，回溯搜索按它（不超过max_solutions）预先分配解的容器，
+    /// 避免解很多时反复扩容；预分配的空间计入内存限额，超出限额时忽略该提示
+    ///
+    /// 求解期间释放GIL：多个Python线程可以同时在各自的核心上求解，互不阻塞，
+    /// 其他线程也能随时调用stop_execution。每次求解只占用调用线程，不使用全局线程池
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10, expected_solutions=None)")]
+    pub fn find_subsets(&self, py: Python, numbers: Vec<i64>, target: i64, max_solutions: Option<usize>, expected_solutions: Option<usize>) -> PyResult<Vec<Vec<usize>>> {
+        self.run_interruptible(py, || self.find_subsets_cached(&numbers, target, max_solutions.unwrap_or(10), expected_solutions.unwrap_or(0)))
+    }
+
+    /// 与find_subsets相同，但每个解直接给出所选的原始整数值，且只返回和恰好为target的解
+    ///
+    /// 数据本身是整数（包括写成 1e9 量级浮点数的整数）时使用，不经过浮点缩放和舍入。
+    /// 位运算与动态规划补充的近似解会被去掉，因此解可能少于max_solutions
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10)")]
+    pub fn find_subsets_ints(&self, py: Python, numbers: Vec<i64>, target: i64, max_solutions: Option<usize>) -> PyResult<Vec<Vec<i64>>> {
+        let solutions = self.run_interruptible(py, || self.find_subsets_cached(&numbers, target, max_solutions.unwrap_or(10), 0))?;
+        Ok(solutions.into_iter()
+            .map(|solution| solution.into_iter().map(|i| numbers[i]).collect::<Vec<i64>>())
+            .filter(|values| values.iter().map(|&x| x as i128).sum::<i128>() == target as i128)
+            .collect())
+    }
+
+    /// 估算求解的理论搜索空间，返回 (算法名称, 最坏情况工作量)
+    ///
+    /// 算法选择与find_subsets完全一致："bitmask" 为 2^n 个组合，"dp" 为 n*(target+1) 个格子，
+    /// "backtracking" 为 2^n 个节点的上界（剪枝后通常远小于此值）。结果超出u128时饱和
+    fn search_space_size(&self, numbers: Vec<i64>, target: i64) -> (&'static str, u128) {
+        let (algorithm, work) = self.estimate_work(&numbers, target);
+        (algorithm.name(), work)
+    }
+
+    /// 在时间预算内求解，返回(解列表, 是否为近似结果)
+    ///
+    /// 先估算所选算法的工作量，位运算或动态规划预计无法在预算内完成时直接使用贪心启发式；
+    /// 否则尝试精确搜索（回溯的最坏估计过于宽松，总是先尝试）。超时后停止搜索，返回已找到的精确解
+    /// （可能不完整）；一个精确解都没有找到时才改用贪心启发式。
+    /// 超时只停止这一次求解，不影响同一实例上其他线程的求解；与find_subsets一样可被Ctrl-C中断。
+    /// 近似结果只包含一个和不超过目标的贪心解，和可能不等于目标
+    #[pyo3(text_signature = "(numbers, target, max_millis, max_solutions=10)")]
+    pub fn solve_within(&self, py: Python, numbers: Vec<i64>, target: i64, max_millis: u64, max_solutions: Option<usize>) -> PyResult<(Vec<Vec<usize>>, bool)> {
+        let heuristic = || {
+            let (solution, _) = greedy_closest_under(&numbers, target);
+            (vec![solution], true)
+        };
+        
+        let (algorithm, work) = self.estimate_work(&numbers, target);
+        if algorithm != Algorithm::BacktrackingCompact && work / WORK_UNITS_PER_MILLI > max_millis as u128 * 10 {
+            return Ok(heuristic());
+        }
+        
+        let deadline = Instant::now() + Duration::from_millis(max_millis);
+        let (mut solutions, timed_out) = self.run_interruptible(py, || {
+            with_deadline(deadline, || self.find_subsets_int(&numbers, target, max_solutions.unwrap_or(10)))
+        })?;
+        
+        if timed_out {
+            // 位运算会在停止后补充近似组合，这里只保留精确解
+            solutions.retain(|solution| solution.iter().map(|&i| numbers[i] as i128).sum::<i128>() == target as i128);
+            if solutions.is_empty() {
+                return Ok(heuristic());
+            }
+        }
+        Ok((solutions, false))
+    }
+
+    /// 浮点数版本的子集求和，返回解的原始索引列表
+    ///
+    /// 根据输入和目标值的最大小数位数（最多10位）统一缩放为整数后求解，也可用decimals固定小数位数（如金额取2位）。
+    /// rounding指定超出精度部分的舍入方式："round"（默认，四舍五入）、"floor"、"ceil"、"bankers"。
+    ///
+    /// tolerance大于0时返回和落在 target ± tolerance 内的子集（如上游舍入导致差几分钱的对账）。
+    /// 自动检测小数位数时tolerance也参与检测；缩放后的容差向下取整，保证不超出指定的偏差，
+    /// 因此固定的decimals精度低于tolerance时容差会被截断（如decimals=1时0.05变为0）。
+    /// 各算法都按缩放后的区间判断命中，只返回区间内的解（不补充区间外的近似组合），
+    /// 动态规划在区间内每个可达的和各给出一个解；带容差的求解不使用结果缓存
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10, rounding=\"round\", decimals=None, tolerance=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn find_subsets_float(&self, py: Python, numbers: Vec<f64>, target: f64, max_solutions: Option<usize>, rounding: Option<&str>, decimals: Option<u32>, tolerance: Option<f64>) -> PyResult<Vec<Vec<usize>>> {
+        let rounding = RoundingMode::parse(rounding.unwrap_or("round"))?;
+        let max_solutions = max_solutions.unwrap_or(10);
+        let tolerance = tolerance.unwrap_or(0.0);
+        if !(tolerance.is_finite() && tolerance >= 0.0) {
+            return Err(SolverError::UnsupportedOption { option: "tolerance", value: tolerance.to_string() }.into());
+        }
+        if tolerance == 0.0 {
+            let (scaled_numbers, scaled_target, _) = scale_to_integers(&numbers, target, rounding, decimals)?;
+            return self.run_interruptible(py, || self.find_subsets_cached(&scaled_numbers, scaled_target, max_solutions, 0));
+        }
+        
+        let decimals = decimals.unwrap_or_else(|| {
+            numbers.iter().chain([target, tolerance].iter())
+                .filter(|x| x.is_finite())
+                .map(|&x| decimal_places(x))
+                .max()
+                .unwrap_or(0)
+        });
+        let (scaled_numbers, scaled_target, decimals) = scale_to_integers(&numbers, target, rounding, Some(decimals))?;
+        let deviation = scale_deviation(tolerance * 10f64.powi(decimals as i32));
+        let (lower, upper) = (scaled_target.saturating_sub(deviation), scaled_target.saturating_add(deviation));
+        let mut solutions = self.run_interruptible(py, || self.solve_window(&scaled_numbers, lower, upper, max_solutions, 0))?;
+        // 容差被截断为0时区间退化为精确求解，位运算与动态规划会补充近似解，这里同样只保留区间内的解
+        solutions.retain(|s| (lower as i128..=upper as i128).contains(&s.iter().map(|&i| scaled_numbers[i] as i128).sum()));
+        Ok(solutions)
+    }
+
+    /// 与find_subsets_float相同，但每个解直接给出所选的数值而不是索引
+    ///
+    /// round_output为False（默认）时返回原始输入值；为True时返回按检测到的小数位数舍入后的值
+    /// （即缩放后的整数除以10的幂），使输出与输入的表面精度一致，例如不会显示为2.9999999999
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10, rounding=\"round\", decimals=None, round_output=False)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn find_subsets_float_values(&self, py: Python, numbers: Vec<f64>, target: f64, max_solutions: Option<usize>, rounding: Option<&str>, decimals: Option<u32>, round_output: Option<bool>) -> PyResult<Vec<Vec<f64>>> {
+        let rounding = RoundingMode::parse(rounding.unwrap_or("round"))?;
+        let (scaled_numbers, scaled_target, decimals) = scale_to_integers(&numbers, target, rounding, decimals)?;
+        let solutions = self.run_interruptible(py, || self.find_subsets_cached(&scaled_numbers, scaled_target, max_solutions.unwrap_or(10), 0))?;
+        let scale = 10f64.powi(decimals as i32);
+        let value = |i: usize| if round_output.unwrap_or(false) { scaled_numbers[i] as f64 / scale } else { numbers[i] };
+        Ok(solutions.into_iter().map(|solution| solution.into_iter().map(value).collect()).collect())
+    }
+
+    /// 浮点数版本的子集求和，只返回和恰好为target的解，每个解为升序的原始索引
+    ///
+    /// 与find_subsets_float不同，不补充近似解，也不合并相同数值（不受merge_duplicates影响）：
+    /// 数值相同但位置不同的组合都是独立的解（如 [2, 2, 2, 3] 目标4得到 [0, 1]、[0, 2]、[1, 2]），
+    /// 适合每个元素代表一笔独立交易的场景。缩放方式与find_subsets_float相同，固定使用回溯搜索
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10)")]
+    pub fn find_subset_indices(&self, py: Python, numbers: Vec<f64>, target: f64, max_solutions: Option<usize>) -> PyResult<Vec<Vec<usize>>> {
+        let (scaled_numbers, scaled_target, _) = scale_to_integers(&numbers, target, RoundingMode::Round, None)?;
+        self.run_interruptible(py, || self.all_position_subsets(&scaled_numbers, scaled_target, max_solutions.unwrap_or(10)))
+    }
+
+    /// 与find_subsets_float相同，但numbers可以是任意可迭代对象（如生成器），逐个读取并缩放
+    ///
+    /// 只保留缩放后的整数，Python端不必先构造完整的浮点数列表，避免两份数据同时驻留内存；
+    /// 缩放方式与find_subsets_float完全一致，求解前仍会完整读取一次输入
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10, rounding=\"round\", decimals=None)")]
+    pub fn find_subsets_float_iter(&self, py: Python, numbers: &PyAny, target: f64, max_solutions: Option<usize>, rounding: Option<&str>, decimals: Option<u32>) -> PyResult<Vec<Vec<usize>>> {
+        let rounding = RoundingMode::parse(rounding.unwrap_or("round"))?;
+        let items = numbers.iter()?.map(|item| item?.extract::<f64>());
+        let (scaled_numbers, scaled_target, _) = scale_stream(items, target, rounding, decimals)?;
+        self.run_interruptible(py, || self.find_subsets_cached(&scaled_numbers, scaled_target, max_solutions.unwrap_or(10), 0))
+    }
+
+    /// 分组选择：从每个组中恰好选一个数，使和为target（如产品配置报价），每个解为各组所选元素在组内的索引
+    ///
+    /// 搜索空间是各组的笛卡尔积而不是子集。数值按find_subsets_float的方式统一缩放；
+    /// 按各组最小值与最大值的后缀和剪枝：当前和加上剩余组的最小值之和已超过目标、或最大值之和仍达不到目标时回退。
+    /// 有空组时无解；没有组时只有目标为0才有一个空解
+    #[pyo3(text_signature = "(groups, target, max_solutions=10)")]
+    pub fn find_subsets_grouped(&self, py: Python, groups: Vec<Vec<f64>>, target: f64, max_solutions: Option<usize>) -> PyResult<Vec<Vec<usize>>> {
+        let flat: Vec<f64> = groups.iter().flatten().copied().collect();
+        let (scaled, scaled_target, _) = scale_to_integers(&flat, target, RoundingMode::Round, None)?;
+        let mut values = scaled.into_iter();
+        let groups: Vec<Vec<i64>> = groups.iter().map(|g| values.by_ref().take(g.len()).collect()).collect();
+        
+        self.reset_progress(flat.len());
+        self.record_strategy(Algorithm::BacktrackingCompact.name());
+        let max_solutions = max_solutions.unwrap_or(10);
+        Ok(py.allow_threads(|| self.one_per_group(&groups, scaled_target, max_solutions)))
+    }
+
+    /// 批量校验外部给出的候选解：每个索引列表的和是否恰好为target，返回与subsets一一对应的布尔列表
+    ///
+    /// numbers和target只按find_subsets_float的方式缩放一次，所有候选解共用；求和使用i128，不会溢出。
+    /// 索引越界或重复的候选解视为不成立。校验是线性的，在调用线程上顺序完成（释放GIL），不使用线程池
+    #[pyo3(text_signature = "(numbers, subsets, target)")]
+    pub fn verify_subsets(&self, py: Python, numbers: Vec<f64>, subsets: Vec<Vec<usize>>, target: f64) -> PyResult<Vec<bool>> {
+        let (scaled_numbers, scaled_target, _) = scale_to_integers(&numbers, target, RoundingMode::Round, None)?;
+        Ok(py.allow_threads(|| {
+            let mut used = vec![false; scaled_numbers.len()];
+            subsets.iter().map(|subset| {
+                let n = used.len();
+                let valid = subset.iter().all(|&i| i < n && !std::mem::replace(&mut used[i], true))
+                    && subset.iter().map(|&i| scaled_numbers[i] as i128).sum::<i128>() == scaled_target as i128;
+                // 恢复标记供下一个候选使用（提前失败时部分索引未标记，清零无害）
+                for &i in subset.iter().filter(|&&i| i < n) {
+                    used[i] = false;
+                }
+                valid
+            }).collect()
+        }))
+    }
+
+    /// 近似求和：返回和与target之差不超过允许偏差的子集 [(索引列表, 实际的和), ...]
+    ///
+    /// 允许偏差与math.isclose相同，取 max(rel_tolerance * |target|, tolerance)：tolerance为绝对容差，
+    /// rel_tolerance为相对目标值的比例（如0.001即0.1%）。缩放时tolerance也参与小数位数检测，
+    /// 相对容差在缩放后按整数目标值计算；两者都向下取整到缩放后的整数，保证不超出指定的偏差。
+    /// target为0时相对容差没有意义，只使用绝对容差。只使用回溯算法，只考虑正数
+    #[pyo3(text_signature = "(numbers, target, tolerance=0.0, rel_tolerance=None, max_solutions=10)")]
+    pub fn find_subsets_approx(&self, py: Python, numbers: Vec<f64>, target: f64, tolerance: Option<f64>, rel_tolerance: Option<f64>, max_solutions: Option<usize>) -> PyResult<Vec<(Vec<usize>, f64)>> {
+        let tolerance = tolerance.unwrap_or(0.0);
+        for value in std::iter::once(tolerance).chain(rel_tolerance) {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(SolverError::UnsupportedOption { option: "tolerance", value: value.to_string() }.into());
+            }
+        }
+        let decimals = numbers.iter().chain([target, tolerance].iter())
+            .filter(|x| x.is_finite())
+            .map(|&x| decimal_places(x))
+            .max()
+            .unwrap_or(0);
+        let (scaled_numbers, scaled_target, decimals) = scale_to_integers(&numbers, target, RoundingMode::Round, Some(decimals))?;
+        let scale = 10f64.powi(decimals as i32);
+        
+        let absolute = scale_deviation(tolerance * scale);
+        let relative = match rel_tolerance {
+            Some(rel) if scaled_target != 0 => scale_deviation(rel * scaled_target.unsigned_abs() as f64),
+            _ => 0,
+        };
+        let deviation = absolute.max(relative);
+        
+        self.reset_progress(scaled_numbers.len());
+        let request = SearchRequest::new(
+            scaled_target.saturating_sub(deviation),
+            scaled_target.saturating_add(deviation),
+            max_solutions.unwrap_or(10),
+        );
+        let solutions = py.allow_threads(|| self.run_backtracking(&scaled_numbers, &request));
+        self.take_callback_error()?;
+        Ok(solutions.into_iter().map(|(solution, sum)| (solution, sum as f64 / scale)).collect())
+    }
+
+    /// 以布尔掩码矩阵的形式返回解，供Python端转换为NumPy数组
+    ///
+    /// 返回 (data, 行数, 列数)：data为按行排列的字节串，每行对应一个解，第j个字节为1表示选中numbers[j]。
+    /// 矩阵在Rust端生成后复制一次到字节串（不依赖rust-numpy）；Python端可用
+    /// numpy.frombuffer(data, dtype=bool).reshape(行数, 列数) 不再复制地得到二维布尔数组，
+    /// 该数组与字节串共享内存，因此是只读的，需要修改时先调用 .copy()。
+    /// 求解与find_subsets相同：可被Ctrl-C中断，求解中的panic转换为INTERNAL_ERROR异常
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10)")]
+    pub fn find_subsets_mask_bytes(&self, py: Python, numbers: Vec<i64>, target: i64, max_solutions: Option<usize>) -> PyResult<(PyObject, usize, usize)> {
+        let cols = numbers.len();
+        let solutions = self.run_interruptible(py, || self.find_subsets_cached(&numbers, target, max_solutions.unwrap_or(10), 0))?;
+        let mut data = vec![0u8; solutions.len() * cols];
+        for (row, solution) in data.chunks_mut(cols.max(1)).zip(&solutions) {
+            for &i in solution {
+                row[i] = 1;
+            }
+        }
+        Ok((PyBytes::new(py, &data).into(), solutions.len(), cols))
+    }
+
+    /// 把输入视为多重集，返回和为target的数值组合（每个组合按数值降序），任意两个结果的数值都不同
+    ///
+    /// 与按索引输出不同：[1, 1, 2] 目标3时只返回 [2, 1] 一次，而不是每种索引配对各一次。
+    /// 搜索直接在(数值, 个数)分组上进行，重复的分支在搜索中就被剪掉，而不是事后去重，
+    /// 因此大量重复值的输入也能高效求解。不需要事先调用set_merge_duplicates
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10)")]
+    pub fn find_value_multisets(&self, py: Python, numbers: Vec<i64>, target: i64, max_solutions: Option<usize>) -> PyResult<Vec<Vec<i64>>> {
+        self.reset_progress(numbers.len());
+        let request = SearchRequest {
+            canonical: true,
+            ..SearchRequest::new(target, target, max_solutions.unwrap_or(10))
+        };
+        let solutions = py.allow_threads(|| self.run_backtracking(&numbers, &request));
+        self.take_callback_error()?;
+        Ok(solutions.into_iter()
+            .map(|(solution, _)| {
+                let mut values: Vec<i64> = solution.into_iter().map(|i| numbers[i]).collect();
+                values.sort_unstable_by(|a, b| b.cmp(a));
+                values
+            })
+            .collect())
+    }
+
+    /// 求解并直接返回外部标识：ids与numbers一一对应（整数、字符串等任意Python对象），
+    /// 每个解是所选元素的ids列表，顺序与find_subsets返回的索引一致
+    #[pyo3(text_signature = "(numbers, ids, target, max_solutions=10)")]
+    pub fn find_subsets_with_ids(&self, py: Python, numbers: Vec<i64>, ids: Vec<PyObject>, target: i64, max_solutions: Option<usize>) -> PyResult<Vec<Vec<PyObject>>> {
+        if ids.len() != numbers.len() {
+            return Err(SolverError::LengthMismatch { numbers: numbers.len(), other: ids.len() }.into());
+        }
+        let solutions = self.find_subsets(py, numbers, target, max_solutions, None)?;
+        Ok(solutions.into_iter()
+            .map(|solution| solution.into_iter().map(|i| ids[i].clone_ref(py)).collect())
+            .collect())
+    }
+
+    /// 以紧凑格式返回解：元素索引连续的解返回半开区间 (start, end)，即numbers[start:end]，其余解返回索引列表
+    ///
+    /// contiguous_only为True时只搜索连续子数组（前缀和+哈希表，O(n)加上输出规模，支持负数），
+    /// 所有解都以区间返回，按结束位置、再按开始位置升序；否则与find_subsets的求解相同，只转换输出格式。
+    /// 元素个数限制与空集/全集设置同样适用于连续模式
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10, contiguous_only=False)")]
+    pub fn find_subsets_compact(&self, py: Python, numbers: Vec<i64>, target: i64, max_solutions: Option<usize>, contiguous_only: Option<bool>) -> PyResult<Vec<PyObject>> {
+        if contiguous_only.unwrap_or(false) {
+            let max_solutions = max_solutions.unwrap_or(10);
+            return Ok(py.allow_threads(|| self.contiguous_ranges(&numbers, target, max_solutions))
+                .into_iter()
+                .map(|range| range.into_py(py))
+                .collect());
+        }
+        Ok(self.find_subsets(py, numbers, target, max_solutions, None)?.into_iter()
+            .map(|mut solution| {
+                solution.sort_unstable();
+                match (solution.first(), solution.last()) {
+                    (Some(&start), Some(&end)) if end - start + 1 == solution.len() => (start, end + 1).into_py(py),
+                    _ => solution.into_py(py),
+                }
+            })
+            .collect())
+    }
+
+    /// 按绝对值求解：-5与5同样计为5，每个解以 [(原始索引, 原始带符号数值), ...] 返回
+    ///
+    /// 每个元素的符号是固定的，只是匹配时只看大小，适合按金额大小对账、不区分借贷方向的规则。
+    /// 算法选择、解的个数等其余行为与find_subsets相同
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10)")]
+    pub fn find_subsets_abs(&self, py: Python, numbers: Vec<i64>, target: i64, max_solutions: Option<usize>) -> PyResult<Vec<Vec<(usize, i64)>>> {
+        // 绝对值之和不超出i64时每个绝对值也不会溢出（排除i64::MIN）
+        if !fits_i64(&numbers) {
+            return Err(SolverError::SumOverflow.into());
+        }
+        let magnitudes: Vec<i64> = numbers.iter().map(|x| x.abs()).collect();
+        let solutions = self.find_subsets(py, magnitudes, target, max_solutions, None)?;
+        Ok(solutions.into_iter()
+            .map(|solution| solution.into_iter().map(|i| (i, numbers[i])).collect())
+            .collect())
+    }
+
+    /// 求解并为每个解附带未使用元素的个数：[(索引列表, 未使用个数, 未使用索引或None), ...]
+    ///
+    /// include_unused为True时第三项为未使用元素的索引列表（升序），否则为None
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10, include_unused=False)")]
+    pub fn find_subsets_with_unused(&self, py: Python, numbers: Vec<i64>, target: i64, max_solutions: Option<usize>, include_unused: Option<bool>) -> PyResult<Vec<UnusedSolution>> {
+        let n = numbers.len();
+        let solutions = self.find_subsets(py, numbers, target, max_solutions, None)?;
+        let include_unused = include_unused.unwrap_or(false);
+        let mut used = vec![false; n];
+        Ok(solutions.into_iter()
+            .map(|solution| {
+                let unused_count = n - solution.len();
+                let unused = include_unused.then(|| {
+                    used.iter_mut().for_each(|u| *u = false);
+                    for &i in &solution {
+                        used[i] = true;
+                    }
+                    (0..n).filter(|&i| !used[i]).collect()
+                });
+                (solution, unused_count, unused)
+            })
+            .collect())
+    }
+
+    /// 只在数值落在 [value_min, value_max] 内的元素中求解，忽略异常值：返回 (解列表, 被排除的索引)
+    ///
+    /// 两个边界都可省略（不限制）；解和被排除的索引都是原始索引（升序）。
+    /// 与预处理中按目标值的过滤不同，这里的范围由调用者指定，过滤后按find_subsets求解
+    #[pyo3(text_signature = "(numbers, target, value_min=None, value_max=None, max_solutions=10)")]
+    pub fn find_subsets_in_value_range(&self, py: Python, numbers: Vec<i64>, target: i64, value_min: Option<i64>, value_max: Option<i64>, max_solutions: Option<usize>) -> PyResult<(Vec<Vec<usize>>, Vec<usize>)> {
+        let in_range = |x: i64| value_min.is_none_or(|low| x >= low) && value_max.is_none_or(|high| x <= high);
+        let (kept, excluded): (Vec<usize>, Vec<usize>) = (0..numbers.len()).partition(|&i| in_range(numbers[i]));
+        let values: Vec<i64> = kept.iter().map(|&i| numbers[i]).collect();
+        let solutions = self.find_subsets(py, values, target, max_solutions, None)?;
+        let solutions = solutions.into_iter()
+            .map(|solution| {
+                let mut original: Vec<usize> = solution.into_iter().map(|k| kept[k]).collect();
+                original.sort_unstable();
+                original
+            })
+            .collect();
+        Ok((solutions, excluded))
+    }
+
+    /// 自动识别输入类型的求解入口
+    ///
+    /// numbers和target全部是Python int时直接走整数求解，保持完整的i64精度（超过2^53的大整数不会失真）；
+    /// 只要出现float就按find_subsets_float的默认方式缩放求解。两条路径的中断和panic处理与find_subsets相同；
+    /// 输入或目标中有bool（True/False）时抛出BOOL_INPUT错误，而不是把它当作1/0
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10)")]
+    pub fn find_subsets_numeric(&self, py: Python, numbers: Vec<&PyAny>, target: &PyAny, max_solutions: Option<usize>) -> PyResult<Vec<Vec<usize>>> {
+        // bool是int的子类，不拒绝的话True/False会被当作1/0求解
+        if std::iter::once(target).chain(numbers.iter().copied()).any(|x| x.downcast::<PyBool>().is_ok()) {
+            return Err(SolverError::BoolInput.into());
+        }
+        let all_ints = target.downcast::<PyLong>().is_ok()
+            && numbers.iter().all(|x| x.downcast::<PyLong>().is_ok());
+        if all_ints {
+            let ints = numbers.iter().map(|x| x.extract::<i64>()).collect::<PyResult<Vec<i64>>>()?;
+            let target: i64 = target.extract()?;
+            return self.run_interruptible(py, || self.find_subsets_cached(&ints, target, max_solutions.unwrap_or(10), 0));
+        }
+        let floats = numbers.iter().map(|x| x.extract::<f64>()).collect::<PyResult<Vec<f64>>>()?;
+        self.find_subsets_float(py, floats, target.extract()?, max_solutions, None, None, None)
+    }
+
+    /// 依次求解多个目标：每个目标找到一个解后从列表中移除这些元素，再在剩余元素中求解下一个目标
+    ///
+    /// 返回与targets等长的列表，第k项为第k个目标选中的原始索引（升序）；某一阶段无解时该项及之后各项为None。
+    /// 输入与全部目标按统一的小数位数缩放为整数（同find_subsets_float的自动检测），
+    /// 每个阶段只取一个解，不回头尝试其他选择，因此前面的选择可能导致后面的阶段无解
+    #[pyo3(text_signature = "(numbers, targets)")]
+    pub fn solve_cascade(&self, py: Python, numbers: Vec<f64>, targets: Vec<f64>) -> PyResult<Vec<Option<Vec<usize>>>> {
+        if targets.iter().any(|t| !t.is_finite()) {
+            return Err(SolverError::NonFiniteInput.into());
+        }
+        let decimals = numbers.iter().chain(&targets).map(|&x| decimal_places(x)).max().unwrap_or(0);
+        let (scaled_numbers, _, _) = scale_to_integers(&numbers, 0.0, RoundingMode::Round, Some(decimals))?;
+        let scaled_targets = targets.iter()
+            .map(|&target| scale_to_integers(&[], target, RoundingMode::Round, Some(decimals)).map(|(_, t, _)| t))
+            .collect::<PyResult<Vec<i64>>>()?;
+        
+        Ok(py.allow_threads(|| {
+            let mut remaining: Vec<usize> = (0..scaled_numbers.len()).collect();
+            let mut chosen = Vec::with_capacity(scaled_targets.len());
+            for &target in &scaled_targets {
+                let values: Vec<i64> = remaining.iter().map(|&i| scaled_numbers[i]).collect();
+                // 位运算与动态规划在无精确解时会补充近似解，这里只接受和恰好为目标的解
+                let found = self.find_subsets_int(&values, target, 1).into_iter()
+                    .find(|solution| solution.iter().map(|&k| values[k]).sum::<i64>() == target);
+                let Some(solution) = found else {
+                    break;
+                };
+                let mut used = vec![false; remaining.len()];
+                for &k in &solution {
+                    used[k] = true;
+                }
+                let mut original: Vec<usize> = solution.iter().map(|&k| remaining[k]).collect();
+                original.sort_unstable();
+                chosen.push(Some(original));
+                remaining = remaining.iter().zip(&used).filter(|&(_, &u)| !u).map(|(&i, _)| i).collect();
+            }
+            chosen.resize(scaled_targets.len(), None);
+            chosen
+        }))
+    }
+
+    /// 精确有理数求解：numbers和target为fractions.Fraction（或int），通分为整数后精确求解
+    ///
+    /// 每个解是所选的原始对象列表（即传入的Fraction对象本身），顺序与find_subsets返回的索引一致。
+    /// 分子分母超出i128，或公分母、缩放后的分子超出i64时抛出RATIONAL_OVERFLOW错误
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10)")]
+    pub fn find_subsets_fraction(&self, py: Python, numbers: Vec<&PyAny>, target: &PyAny, max_solutions: Option<usize>) -> PyResult<Vec<Vec<PyObject>>> {
+        let parts = |x: &PyAny| -> PyResult<(i128, i128)> {
+            let num = x.getattr("numerator")?.extract::<i128>().map_err(|_| SolverError::RationalOverflow)?;
+            let den = x.getattr("denominator")?.extract::<i128>().map_err(|_| SolverError::RationalOverflow)?;
+            if den <= 0 {
+                return Err(SolverError::UnsupportedOption { option: "denominator", value: den.to_string() }.into());
+            }
+            Ok((num, den))
+        };
+        let rationals = numbers.iter().map(|&x| parts(x)).collect::<PyResult<Vec<(i128, i128)>>>()?;
+        let (scaled_numbers, scaled_target, _) = scale_rationals(&rationals, parts(target)?)?;
+        let solutions = self.find_subsets(py, scaled_numbers, scaled_target, max_solutions, None)?;
+        Ok(solutions.into_iter()
+            .map(|solution| solution.into_iter().map(|i| numbers[i].into_py(py)).collect())
+            .collect())
+    }
+
+    /// 查找和落在闭区间 [low, high] 内的子集
+    ///
+    /// minimal_only为True时只返回极小解：子集的和在区间内，且它的任何真子集的和都不在区间内。
+    /// 由于只考虑正数，等价于去掉子集中最小的元素后和小于low。
+    /// distinct_sums_only为True时每个不同的和只返回第一个找到的解，结果最多 high-low+1 个。
+    /// divisible_by要求和同时是它的倍数（必须为正），区间先收紧到最近的倍数再搜索
+    #[pyo3(text_signature = "(numbers, low, high, max_solutions=10, minimal_only=False, distinct_sums_only=False, divisible_by=None)")]
+    #[allow(clippy::too_many_arguments)] // 参数与Python端的关键字参数一一对应
+    pub fn find_subsets_in_range(&self, py: Python, numbers: Vec<i64>, low: i64, high: i64, max_solutions: Option<usize>, minimal_only: Option<bool>, distinct_sums_only: Option<bool>, divisible_by: Option<i64>) -> PyResult<Vec<Vec<usize>>> {
+        Ok(Self::strip_sums(self.find_subsets_in_range_with_sums(py, numbers, low, high, max_solutions, minimal_only, distinct_sums_only, divisible_by)?))
+    }
+
+    /// 查找和不超过cap的子集（预算类查询："花X元以内能买哪些"），即 [0, cap] 区间查询
+    ///
+    /// 满足条件的子集数量通常极大，建议配合set_size_limits或较小的max_solutions使用
+    #[pyo3(text_signature = "(numbers, cap, max_solutions=10)")]
+    pub fn find_subsets_under(&self, py: Python, numbers: Vec<i64>, cap: i64, max_solutions: Option<usize>) -> PyResult<Vec<Vec<usize>>> {
+        self.find_subsets_in_range(py, numbers, 0, cap, max_solutions, None, None, None)
+    }
+
+    /// 按和从大到小返回和不超过cap的子集：[(索引列表, 和), ...]（"尽量花完预算"）
+    ///
+    /// 使用最优优先搜索：优先队列按乐观上界 min(cap, 当前和 + 剩余之和) 排序，
+    /// 完整子集的上界就是它的和，因此出队顺序即和的降序，只需搜索前max_solutions个需要的部分
+    #[pyo3(text_signature = "(numbers, cap, max_solutions=10)")]
+    pub fn find_subsets_under_by_sum(&self, py: Python, numbers: Vec<i64>, cap: i64, max_solutions: Option<usize>) -> Vec<(Vec<usize>, i64)> {
+        self.reset_progress(numbers.len());
+        if cap < 0 {
+            return Vec::new();
+        }
+        py.allow_threads(|| self.best_first_range(&numbers, 0, cap, cap, max_solutions.unwrap_or(10)))
+    }
+
+    /// 按和与preferred的距离从近到远返回和落在 [low, high] 内的子集：[(索引列表, 和), ...]
+    ///
+    /// preferred默认为high。与find_subsets_under_by_sum使用同一个最优优先搜索，解按顺序产生，
+    /// 不需要先收集全部解再排序；代价是优先队列中的待展开节点，每个约数十字节，
+    /// 计入set_memory_limit的限额，超出时返回已找到的部分
+    #[pyo3(text_signature = "(numbers, low, high, preferred=None, max_solutions=10)")]
+    pub fn find_subsets_in_range_by_proximity(&self, py: Python, numbers: Vec<i64>, low: i64, high: i64, preferred: Option<i64>, max_solutions: Option<usize>) -> Vec<(Vec<usize>, i64)> {
+        self.reset_progress(numbers.len());
+        let low = low.max(0);
+        if low > high {
+            return Vec::new();
+        }
+        let preferred = preferred.unwrap_or(high);
+        py.allow_threads(|| self.best_first_range(&numbers, low, high, preferred, max_solutions.unwrap_or(10)))
+    }
+
+    /// 一次搜索同时返回精确解和最接近的非精确解：(精确解列表, [(索引列表, 和), ...])
+    ///
+    /// 回溯搜索 [target - tolerance, target + tolerance] 区间，和恰好为target的解照常记录（最多max_solutions个，
+    /// 达到后停止搜索）；其余的解只在一个有界堆中保留离target最近的max_misses个，按距离从近到远排列，
+    /// 适合对账时"先给精确匹配，再给几个近似建议"。tolerance必须非负，越大搜索范围越大
+    #[pyo3(text_signature = "(numbers, target, tolerance, max_solutions=10, max_misses=5)")]
+    pub fn find_subsets_with_near_misses(&self, py: Python, numbers: Vec<i64>, target: i64, tolerance: i64, max_solutions: Option<usize>, max_misses: Option<usize>) -> PyResult<ExactAndNearMisses> {
+        if tolerance < 0 {
+            return Err(SolverError::UnsupportedOption { option: "tolerance", value: tolerance.to_string() }.into());
+        }
+        self.reset_progress(numbers.len());
+        let collector = SolutionCollector {
+            near_misses: Some(NearMisses::new(target, max_misses.unwrap_or(5))),
+            ..SolutionCollector::default()
+        };
+        let request = SearchRequest::new(target.saturating_sub(tolerance), target.saturating_add(tolerance), max_solutions.unwrap_or(10));
+        let collector = self.run_interruptible(py, || self.run_backtracking_into(&numbers, &request, collector))?;
+        self.memory_tracker.deallocate(collector.tracked_bytes);
+        let misses = collector.near_misses.map(NearMisses::into_sorted).unwrap_or_default();
+        Ok((Self::strip_sums(collector.solutions), misses))
+    }
+
+    /// 与find_subsets_in_range相同，但每个解附带其实际的和：[(索引列表, 和), ...]
+    #[pyo3(text_signature = "(numbers, low, high, max_solutions=10, minimal_only=False, distinct_sums_only=False, divisible_by=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn find_subsets_in_range_with_sums(&self, py: Python, numbers: Vec<i64>, low: i64, high: i64, max_solutions: Option<usize>, minimal_only: Option<bool>, distinct_sums_only: Option<bool>, divisible_by: Option<i64>) -> PyResult<Vec<(Vec<usize>, i64)>> {
+        if let Some(d) = divisible_by.filter(|&d| d <= 0) {
+            return Err(SolverError::UnsupportedOption { option: "divisor", value: d.to_string() }.into());
+        }
+        self.reset_progress(numbers.len());
+        if low > high {
+            return Ok(Vec::new());
+        }
+        let request = SearchRequest {
+            minimal_only: minimal_only.unwrap_or(false),
+            distinct_sums: distinct_sums_only.unwrap_or(false),
+            divisible_by,
+            ..SearchRequest::new(low, high, max_solutions.unwrap_or(10))
+        };
+        self.run_interruptible(py, || self.run_backtracking(&numbers, &request))
+    }
+
+    /// 与find_subsets_in_range相同，但按解的实际和分组：[(和, [索引列表, ...]), ...]，按和升序排列
+    #[pyo3(text_signature = "(numbers, low, high, max_solutions=10, minimal_only=False, distinct_sums_only=False, divisible_by=None)")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn find_subsets_in_range_grouped(&self, py: Python, numbers: Vec<i64>, low: i64, high: i64, max_solutions: Option<usize>, minimal_only: Option<bool>, distinct_sums_only: Option<bool>, divisible_by: Option<i64>) -> PyResult<Vec<(i64, Vec<Vec<usize>>)>> {
+        let solutions = self.find_subsets_in_range_with_sums(py, numbers, low, high, max_solutions, minimal_only, distinct_sums_only, divisible_by)?;
+        let mut groups: BTreeMap<i64, Vec<Vec<usize>>> = BTreeMap::new();
+        for (solution, sum) in solutions {
+            groups.entry(sum).or_default().push(solution);
+        }
+        Ok(groups.into_iter().collect())
+    }
+
+    /// 查找和为target且至少包含一个锚点索引的子集
+    ///
+    /// anchor为原始索引列表，只接受包含其中至少一个索引的解；始终使用回溯算法，
+    /// 在未选中锚点且剩余数字中已无锚点时剪枝
+    #[pyo3(text_signature = "(numbers, target, anchor, max_solutions=10)")]
+    pub fn find_subsets_with_anchor(&self, py: Python, numbers: Vec<i64>, target: i64, anchor: Vec<usize>, max_solutions: Option<usize>) -> PyResult<Vec<Vec<usize>>> {
+        let mut anchors = vec![false; numbers.len()];
+        for &i in &anchor {
+            if i >= numbers.len() {
+                return Err(SolverError::IndexOutOfRange { index: i, len: numbers.len() }.into());
+            }
+            anchors[i] = true;
+        }
+        self.reset_progress(numbers.len());
+        let request = SearchRequest {
+            anchors: &anchors,
+            ..SearchRequest::new(target, target, max_solutions.unwrap_or(10))
+        };
+        let solutions = py.allow_threads(|| self.run_backtracking(&numbers, &request));
+        self.take_callback_error()?;
+        Ok(Self::strip_sums(solutions))
+    }
+
+    /// 与find_subsets_with_anchor相同，但按包含的锚点分组返回 {锚点索引: [索引列表, ...]}
+    ///
+    /// 每个锚点都有一项（没有解时为空列表）；包含多个锚点的解出现在每个对应的分组中。
+    /// max_solutions限制的是不同解的总数，而不是每个分组的解数
+    #[pyo3(text_signature = "(numbers, target, anchor, max_solutions=10)")]
+    pub fn find_subsets_by_anchor(&self, py: Python, numbers: Vec<i64>, target: i64, anchor: Vec<usize>, max_solutions: Option<usize>) -> PyResult<BTreeMap<usize, Vec<Vec<usize>>>> {
+        let mut buckets: BTreeMap<usize, Vec<Vec<usize>>> = anchor.iter().map(|&i| (i, Vec::new())).collect();
+        for solution in self.find_subsets_with_anchor(py, numbers, target, anchor, max_solutions)? {
+            for i in &solution {
+                if let Some(bucket) = buckets.get_mut(i) {
+                    bucket.push(solution.clone());
+                }
+            }
+        }
+        Ok(buckets)
+    }
+
+    /// 分页获取解：在后台线程上运行一次回溯搜索，返回SolutionPager按页拉取
+    ///
+    /// 搜索只运行一次，通道中最多缓存page_size个解，Python端取走之前搜索暂停（背压），不会把全部解放在内存中。
+    /// cursor为已经取走的解的个数：解的顺序是确定的，传入上一次的cursor即可在新的请求中从断点继续
+    /// （跳过的解仍需重新搜索，但不会被保存或传回）。后台搜索使用当前求解器设置的副本，不调用解回调
+    #[pyo3(text_signature = "(numbers, target, page_size=100, cursor=0)")]
+    pub fn paginate(&self, numbers: Vec<i64>, target: i64, page_size: Option<usize>, cursor: Option<usize>) -> PyResult<SolutionPager> {
+        let page_size = page_size.unwrap_or(100).max(1);
+        let cursor = cursor.unwrap_or(0);
+        let (sender, receiver) = std::sync::mpsc::sync_channel(page_size);
+        let solver = Arc::new(self.clone_settings());
+        let worker_solver = Arc::clone(&solver);
+        std::thread::Builder::new()
+            .name("subset-sum-pager".into())
+            .spawn(move || {
+                worker_solver.reset_progress(numbers.len());
+                let collector = SolutionCollector {
+                    sink: Some(SolutionSink { sender, skip: cursor }),
+                    ..SolutionCollector::default()
+                };
+                let request = SearchRequest::new(target, target, usize::MAX);
+                worker_solver.run_backtracking_into(&numbers, &request, collector);
+            })?;
+        Ok(SolutionPager { solver, receiver: Some(receiver), page_size, cursor })
+    }
+
+    /// FIFO优先：按原始索引升序搜索，先报告使用靠前元素的解
+    ///
+    /// 与find_subsets一样每种数值组合只报告一次，但总是取最靠前的索引，并按索引列表的字典序发现：
+    /// 搜索不按数值排序，限制max_solutions时得到的是最偏向早期元素的那些解（如先进先出的库存）。
+    /// 始终使用回溯算法；不按数值排序时剪枝较弱，大规模输入会比find_subsets慢
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10)")]
+    pub fn find_subsets_fifo(&self, py: Python, numbers: Vec<i64>, target: i64, max_solutions: Option<usize>) -> PyResult<Vec<Vec<usize>>> {
+        self.reset_progress(numbers.len());
+        let request = SearchRequest {
+            fifo: true,
+            ..SearchRequest::new(target, target, max_solutions.unwrap_or(10))
+        };
+        let solutions = py.allow_threads(|| self.run_backtracking(&numbers, &request));
+        self.take_callback_error()?;
+        Ok(Self::strip_sums(solutions))
+    }
+
+    /// 查找和为target且所选元素的有效时间窗有公共时刻的子集
+    ///
+    /// windows[i] = (开始, 结束) 为第i个元素的有效时间窗（闭区间），与numbers等长。
+    /// 始终使用回溯算法，搜索时维护已选元素时间窗的交集，交集为空时剪枝；
+    /// 数值相同但时间窗不同的元素不可互换，因此不与set_merge_duplicates一起合并
+    #[pyo3(text_signature = "(numbers, windows, target, max_solutions=10)")]
+    pub fn find_subsets_timevalid(&self, py: Python, numbers: Vec<i64>, windows: Vec<(i64, i64)>, target: i64, max_solutions: Option<usize>) -> PyResult<Vec<Vec<usize>>> {
+        if windows.len() != numbers.len() {
+            return Err(SolverError::LengthMismatch { numbers: numbers.len(), other: windows.len() }.into());
+        }
+        self.reset_progress(numbers.len());
+        let request = SearchRequest {
+            windows: &windows,
+            ..SearchRequest::new(target, target, max_solutions.unwrap_or(10))
+        };
+        let solutions = py.allow_threads(|| self.run_backtracking(&numbers, &request));
+        self.take_callback_error()?;
+        Ok(Self::strip_sums(solutions))
+    }
+
+    /// 在已锁定部分元素的前提下补全子集，返回包含preselected在内的完整索引列表（升序）
+    ///
+    /// 从target中减去已选元素之和，只在其余元素中用回溯搜索补全，只返回精确解；
+    /// 已选元素之和恰好等于target时，第一个解就是preselected本身。
+    /// 元素个数限制与空集/全集设置作用于补全部分
+    #[pyo3(text_signature = "(numbers, preselected, target, max_solutions=10)")]
+    pub fn complete_subset(&self, py: Python, numbers: Vec<i64>, preselected: Vec<usize>, target: i64, max_solutions: Option<usize>) -> PyResult<Vec<Vec<usize>>> {
+        let max_solutions = max_solutions.unwrap_or(10);
+        let mut locked = vec![false; numbers.len()];
+        let mut locked_sum: i64 = 0;
+        for &i in &preselected {
+            if i >= numbers.len() {
+                return Err(SolverError::IndexOutOfRange { index: i, len: numbers.len() }.into());
+            }
+            if locked[i] {
+                return Err(SolverError::DuplicateIndex { index: i }.into());
+            }
+            locked[i] = true;
+            locked_sum = locked_sum.checked_add(numbers[i])
+                .ok_or(SolverError::SumOverflow)?;
+        }
+        
+        self.reset_progress(numbers.len() - preselected.len());
+        let remainder = match target.checked_sub(locked_sum) {
+            Some(remainder) if remainder >= 0 => remainder,
+            _ => return Ok(Vec::new()),
+        };
+        
+        let mut base = preselected;
+        base.sort_unstable();
+        // 允许空集时回溯会在根节点记录空的补全，这里不再重复加入preselected
+        let mut results = Vec::new();
+        if remainder == 0 && max_solutions > 0 && !base.is_empty() && !self.allow_empty {
+            results.push(base.clone());
+        }
+        
+        // 剩余候选元素，pool[k]为其在numbers中的原始索引
+        let pool: Vec<usize> = (0..numbers.len()).filter(|&i| !locked[i]).collect();
+        let pool_numbers: Vec<i64> = pool.iter().map(|&i| numbers[i]).collect();
+        let request = SearchRequest::new(remainder, remainder, max_solutions - results.len());
+        let completions = py.allow_threads(|| self.run_backtracking(&pool_numbers, &request));
+        self.take_callback_error()?;
+        for (completion, _) in completions {
+            let mut solution = base.clone();
+            solution.extend(completion.into_iter().map(|k| pool[k]));
+            solution.sort_unstable();
+            debug_assert_solution(&numbers, &solution, target, target);
+            results.push(solution);
+        }
+        Ok(results)
+    }
+
+    /// 判断是否存在和恰好为target的子集（只判断可行性，不构造解）
+    ///
+    /// 使用按u64字打包的位集动态规划：reachable |= reachable << x，
+    /// 目标在几十万量级、数字上千个时也能很快完成，不受dp_max_target限制。
+    /// 位集超出内存限制时抛出ValueError，被停止时返回False
+    #[pyo3(text_signature = "(numbers, target)")]
+    pub fn is_target_reachable(&self, py: Python, numbers: Vec<i64>, target: i64) -> PyResult<bool> {
+        if target < 0 {
+            return Ok(false);
+        }
+        let words = usize::try_from(target / 64 + 1)
+            .map_err(|_| SolverError::TargetTooLarge)?;
+        let memory_size = words.saturating_mul(std::mem::size_of::<u64>());
+        if !self.memory_tracker.allocate(memory_size) {
+            return Err(SolverError::MemoryLimit { bytes: memory_size }.into());
+        }
+        
+        self.reset_progress(numbers.len());
+        self.total_combinations.store(numbers.len() as u64, Ordering::SeqCst);
+        let reachable = py.allow_threads(|| self.bitset_reachable(&numbers, target as usize, words));
+        self.memory_tracker.deallocate(memory_size);
+        Ok(reachable)
+    }
+
+    /// 返回一个和恰好为target的子集（升序的原始索引），不存在时返回None
+    ///
+    /// 与is_target_reachable对应的带解版本：动态规划只为每个和记录第一次到达它的元素（u32回指），
+    /// 到达target后沿 和 - numbers[回指] 回溯出唯一的解，每个和只占4字节，而不是为每个和保存一个子集，
+    /// 因此同样的内存限制下可以处理大得多的目标。不考虑元素个数限制；表超出内存限制时抛出MEMORY_LIMIT错误，被停止时返回None
+    #[pyo3(text_signature = "(numbers, target)")]
+    pub fn find_witness(&self, py: Python, numbers: Vec<i64>, target: i64) -> PyResult<Option<Vec<usize>>> {
+        if target < 0 || numbers.len() >= u32::MAX as usize {
+            return Ok(None);
+        }
+        if target == 0 {
+            return Ok(self.allow_empty.then(Vec::new));
+        }
+        let target_usize = usize::try_from(target).map_err(|_| SolverError::TargetTooLarge)?;
+        let memory_size = target_usize.saturating_add(1).saturating_mul(std::mem::size_of::<u32>());
+        if !self.memory_tracker.allocate(memory_size) {
+            return Err(SolverError::MemoryLimit { bytes: memory_size }.into());
+        }
+        
+        self.reset_progress(numbers.len());
+        self.total_combinations.store(numbers.len() as u64, Ordering::SeqCst);
+        let witness = py.allow_threads(|| self.dp_witness(&numbers, target_usize));
+        self.memory_tracker.deallocate(memory_size);
+        Ok(witness)
+    }
+
+    /// 返回最少的一组索引，删除这些数字后不再存在和恰好为target的子集（"作废哪些交易能消除这个匹配"）
+    ///
+    /// 按删除个数k = 0, 1, 2...逐步加深：位集可行性判断（同is_target_reachable）先快速排除不可达的情况，
+    /// 仍可达时用回溯找出一个解，下一个删除的数字必须取自这个解，因此每层只在一个解的元素上分支。
+    /// 结果是最小的删除集合（升序），target为0且允许空集时任何删除都无效，返回None。
+    /// 只支持非负输入（位集判断忽略负数，含负数时结论不成立），含负数时抛出NEGATIVE_INPUT错误；
+    /// 输入全为非负数时负的target本来就不可达，返回空列表。
+    /// 最坏情况为指数级，只适合小规模输入；被停止时返回None
+    #[pyo3(text_signature = "(numbers, target)")]
+    pub fn min_breaking_set(&self, py: Python, numbers: Vec<i64>, target: i64) -> PyResult<Option<Vec<usize>>> {
+        if let Some(i) = numbers.iter().position(|&x| x < 0) {
+            return Err(SolverError::NegativeInput { index: i, value: numbers[i] }.into());
+        }
+        if target < 0 {
+            return Ok(Some(Vec::new()));
+        }
+        let words = usize::try_from(target / 64 + 1)
+            .map_err(|_| SolverError::TargetTooLarge)?;
+        let memory_size = words.saturating_mul(std::mem::size_of::<u64>());
+        if !self.memory_tracker.allocate(memory_size) {
+            return Err(SolverError::MemoryLimit { bytes: memory_size }.into());
+        }
+        
+        self.reset_progress(numbers.len());
+        let result = py.allow_threads(|| {
+            let mut remaining = numbers.clone();
+            let mut removed = Vec::new();
+            for k in 0..=numbers.len() {
+                if self.break_within(&mut remaining, target, words, k, &mut removed) {
+                    removed.sort_unstable();
+                    return Some(removed);
+                }
+                if self.cancelled.load(Ordering::SeqCst) {
+                    break;
+                }
+            }
+            None
+        });
+        self.memory_tracker.deallocate(memory_size);
+        Ok(result)
+    }
+
+    /// 计算解集合的规范哈希，与解的发现顺序以及每个解内索引的顺序无关
+    ///
+    /// 先对每个解内的索引排序，再对解列表排序，然后按 (解长度, 各索引) 的u64小端字节做FNV-1a哈希。
+    /// 结果在不同进程和不同版本之间稳定，可用于回归测试比较两次求解的输出
+    #[staticmethod]
+    #[pyo3(text_signature = "(solutions)")]
+    pub fn solution_set_hash(solutions: Vec<Vec<usize>>) -> u64 {
+        let mut canonical = solutions;
+        for solution in &mut canonical {
+            solution.sort_unstable();
+        }
+        canonical.sort_unstable();
+        let words = canonical.iter().flat_map(|solution| {
+            std::iter::once(solution.len() as u64).chain(solution.iter().map(|&i| i as u64))
+        });
+        fnv1a_64(words.flat_map(u64::to_le_bytes))
+    }
+
+    /// 把数字分成两组，使两组之和的差尽量小（划分问题的优化版本）
+    ///
+    /// 返回 (A组索引, B组索引, 两组和之差, 是否近似)，A组的和不超过B组。
+    /// 精确求解需要约 总和/2 个格子的动态规划表，超出内存限制或被停止时退回贪心算法，此时差值不保证最小。
+    /// 只支持非负数，零放入B组
+    #[pyo3(text_signature = "(numbers)")]
+    pub fn find_balanced_partition(&self, py: Python, numbers: Vec<i64>) -> PyResult<(Vec<usize>, Vec<usize>, i64, bool)> {
+        if let Some(i) = numbers.iter().position(|&x| x < 0) {
+            return Err(SolverError::NegativeInput { index: i, value: numbers[i] }.into());
+        }
+        let total = numbers.iter().try_fold(0i64, |acc, &x| acc.checked_add(x))
+            .ok_or(SolverError::SumOverflow)?;
+        
+        self.reset_progress(numbers.len());
+        let half = total / 2;
+        // 回调抛出异常时停止动态规划，由本方法抛出该异常
+        let mut callback_error = None;
+        let report = |group: &[usize], sum: i64| {
+            let Some(callback) = &self.improvement_callback else {
+                return true;
+            };
+            match Python::with_gil(|py| callback.call1(py, (group.to_vec(), total - 2 * sum))) {
+                Ok(_) => true,
+                Err(err) => {
+                    callback_error = Some(err);
+                    false
+                }
+            }
+        };
+        let (group_a, sum_a, approx) = py.allow_threads(|| match self.closest_under_dp(&numbers, half, report) {
+            Some((group, sum)) => (group, sum, false),
+            None => {
+                let (group, sum) = greedy_closest_under(&numbers, half);
+                (group, sum, true)
+            }
+        });
+        if let Some(err) = callback_error {
+            return Err(err);
+        }
+        
+        let mut in_a = vec![false; numbers.len()];
+        for &i in &group_a {
+            in_a[i] = true;
+        }
+        let group_b = (0..numbers.len()).filter(|&i| !in_a[i]).collect();
+        Ok((group_a, group_b, total - 2 * sum_a, approx))
+    }
+
+    /// 把每个数分配到恰好一个桶中，使每个桶的和与其目标之差不超过tolerance
+    ///
+    /// 返回每个桶的索引列表（升序），无法满足或被停止时返回None。先用贪心（从大到小放入离目标差得最多的桶）
+    /// 尝试，失败后用回溯精确搜索：桶的和超出上限、剩余数字放不满或放不下时剪枝，状态相同的桶只尝试一个。
+    /// 只支持非负数
+    #[pyo3(text_signature = "(numbers, bucket_targets, tolerance=0)")]
+    pub fn allocate_to_buckets(&self, py: Python, numbers: Vec<i64>, bucket_targets: Vec<i64>, tolerance: Option<i64>) -> PyResult<Option<Vec<Vec<usize>>>> {
+        let tolerance = tolerance.unwrap_or(0);
+        if tolerance < 0 {
+            return Err(SolverError::UnsupportedOption { option: "tolerance", value: tolerance.to_string() }.into());
+        }
+        if let Some(i) = numbers.iter().position(|&x| x < 0) {
+            return Err(SolverError::NegativeInput { index: i, value: numbers[i] }.into());
+        }
+        if !fits_i64(&numbers) {
+            return Err(SolverError::SumOverflow.into());
+        }
+        
+        self.reset_progress(numbers.len());
+        let lows: Vec<i64> = bucket_targets.iter().map(|&t| t.saturating_sub(tolerance)).collect();
+        let highs: Vec<i64> = bucket_targets.iter().map(|&t| t.saturating_add(tolerance)).collect();
+        let assignment = py.allow_threads(|| self.assign_buckets(&numbers, &lows, &highs));
+        Ok(assignment.map(|assignment| {
+            let mut buckets = vec![Vec::new(); bucket_targets.len()];
+            for (i, bucket) in assignment.into_iter().enumerate() {
+                buckets[bucket].push(i);
+            }
+            buckets
+        }))
+    }
+
+    /// 快速估计和恰好为target的子集数量（按索引计数，结果饱和到u64），用于决定是否值得枚举
+    ///
+    /// 目标不超过dp_max_target且计数表不超出内存限制时用计数动态规划给出精确值（O(n*target)）；
+    /// 否则用正态近似：随机子集的和近似服从均值 Σx/2、方差 Σx²/4 的正态分布，
+    /// 子集和只落在各数最大公约数g的倍数上，估计值为 2^n * 密度(target) * g，只表示数量级。
+    /// 与其他求解一样只考虑正数，不受元素个数限制影响
+    #[pyo3(text_signature = "(numbers, target)")]
+    pub fn estimate_solution_count(&self, py: Python, numbers: Vec<i64>, target: i64) -> u64 {
+        let positives: Vec<i64> = numbers.into_iter().filter(|&x| x > 0).collect();
+        if target <= 0 {
+            return (target == 0 && self.allow_empty) as u64;
+        }
+        let table_bytes = (target as u64).saturating_add(1).saturating_mul(std::mem::size_of::<u64>() as u64);
+        if target <= self.thresholds.dp_max_target && usize::try_from(table_bytes).is_ok_and(|bytes| self.memory_tracker.allocate(bytes)) {
+            let count = py.allow_threads(|| count_subsets_saturating(&positives, target as usize));
+            self.memory_tracker.deallocate(table_bytes as usize);
+            return count;
+        }
+        estimate_count_normal(&positives, target)
+    }
+
+    /// 统计所有解而不保存解本身，适合解的数量极大的情况
+    ///
+    /// high为None时统计和恰好为target的解，否则统计和落在 [target, high] 内的解。
+    /// 返回 (解数量, 元素个数直方图, 最小和, 最大和)，直方图第k项为含k个元素的解的数量；
+    /// 没有解时最小和与最大和为None。只累计统计不保存解，因此不受内存限制影响
+    #[pyo3(text_signature = "(numbers, target, high=None)")]
+    pub fn subset_statistics(&self, py: Python, numbers: Vec<i64>, target: i64, high: Option<i64>) -> (usize, Vec<u64>, Option<i64>, Option<i64>) {
+        self.reset_progress(numbers.len());
+        let high = high.unwrap_or(target);
+        if target > high {
+            return (0, Vec::new(), None, None);
+        }
+        let collector = SolutionCollector {
+            stats: Some(SolutionStats::default()),
+            ..SolutionCollector::default()
+        };
+        let request = SearchRequest::new(target, high, usize::MAX);
+        let stats = py.allow_threads(|| self.run_backtracking_into(&numbers, &request, collector)).stats.unwrap_or_default();
+        (stats.count, stats.size_histogram, stats.min_sum, stats.max_sum)
+    }
+
+    /// 统计每个元素出现在多少个和为target的解中，返回按原始索引排列的次数列表
+    ///
+    /// 解在搜索中直接累计而不保存，适合解有数百万个、只关心哪些元素"热门"的分析。
+    /// 解的集合与回溯求解相同：数值相同的元素可互换，每种数值组合只计一次，且计在靠前的元素上。
+    /// max_solutions为None时统计全部解
+    #[pyo3(text_signature = "(numbers, target, max_solutions=None)")]
+    pub fn index_frequency(&self, py: Python, numbers: Vec<i64>, target: i64, max_solutions: Option<usize>) -> Vec<u64> {
+        self.reset_progress(numbers.len());
+        let collector = SolutionCollector {
+            stats: Some(SolutionStats::default()),
+            frequency: Some(vec![0; numbers.len()]),
+            ..SolutionCollector::default()
+        };
+        let request = SearchRequest::new(target, target, max_solutions.unwrap_or(usize::MAX));
+        let collector = py.allow_threads(|| self.run_backtracking_into(&numbers, &request, collector));
+        collector.frequency.unwrap_or_default()
+    }
+
+    /// 直接求按字典序排列的第k个解（k从0开始），返回所选的数值，k超出解的数量时返回None
+    ///
+    /// 解按原始索引升序列表的字典序排列（前缀排在其扩展之前），顺序与求解策略无关，可用于分页浏览。
+    /// 先用计数动态规划统计每个后缀能凑出各个和的子集数，再逐位跳过整块解，不需要生成前k个解。
+    /// 计数表约有 (n+1)*(缩放后目标+1) 个格子，超出内存限制时报错；只支持非负数，
+    /// 空集/全集设置同样生效，但不受set_size_limits影响
+    #[pyo3(text_signature = "(numbers, target, k)")]
+    pub fn nth_subset(&self, py: Python, numbers: Vec<f64>, target: f64, k: u128) -> PyResult<Option<Vec<f64>>> {
+        let (scaled_numbers, scaled_target, _) = scale_to_integers(&numbers, target, RoundingMode::Round, None)?;
+        if let Some(i) = scaled_numbers.iter().position(|&x| x < 0) {
+            return Err(SolverError::NegativeInput { index: i, value: scaled_numbers[i] }.into());
+        }
+        let Ok(target) = usize::try_from(scaled_target) else {
+            return Ok(None);
+        };
+        
+        let n = scaled_numbers.len();
+        let cells = (n + 1).saturating_mul(target.saturating_add(1));
+        let memory_size = cells.saturating_mul(std::mem::size_of::<u128>());
+        if !self.memory_tracker.allocate(memory_size) {
+            return Err(SolverError::MemoryLimit { bytes: memory_size }.into());
+        }
+        
+        self.reset_progress(n);
+        self.total_combinations.store(n as u64, Ordering::SeqCst);
+        self.record_strategy(Algorithm::DynamicProgramming.name());
+        let solution = py.allow_threads(|| {
+            let counts = self.suffix_subset_counts(&scaled_numbers, target);
+            Self::unrank_subset(&scaled_numbers, target, &counts, k, self.allow_empty, self.allow_full)
+        });
+        self.memory_tracker.deallocate(memory_size);
+        Ok(solution.map(|solution| solution.into_iter().map(|i| numbers[i]).collect()))
+    }
+
+    /// 带价值的精确装箱：在重量之和恰好为target的子集中，返回价值之和最高的top_k个 [(索引列表, 总价值), ...]
+    ///
+    /// weights为整数重量（目标作用在重量上），values为与之等长的价值，按总价值从高到低排列。
+    /// 即以价值为加法偏好的find_subsets_by_preference，但不合并重量相同的元素：
+    /// 重量组合相同、所选元素（价值）不同的解分别参与排名，因此前top_k个是所有索引组合中真正价值最高的
+    #[pyo3(text_signature = "(weights, values, target, top_k=10)")]
+    pub fn find_subsets_knapsack(&self, py: Python, weights: Vec<i64>, values: Vec<f64>, target: i64, top_k: Option<usize>) -> PyResult<Vec<(Vec<usize>, f64)>> {
+        self.rank_by_preference(py, &weights, values, target, top_k.unwrap_or(10), false, true)
+    }
+
+    /// 按偏好对和恰好为target的子集排序，返回得分最高的top_n个 [(索引列表, 得分), ...]
+    ///
+    /// preferences[i]为第i个元素的偏好（如被采纳的概率），与numbers等长。
+    /// multiplicative为False时得分为所选元素偏好之和，为True时为偏好之积。
+    /// 数值相同的组合只保留偏好最高的一个，例如[5, 5]中只会报告偏好较高的那个5
+    #[pyo3(text_signature = "(numbers, preferences, target, top_n=10, multiplicative=False)")]
+    pub fn find_subsets_by_preference(
+        &self,
+        py: Python,
+        numbers: Vec<i64>,
+        preferences: Vec<f64>,
+        target: i64,
+        top_n: Option<usize>,
+        multiplicative: Option<bool>,
+    ) -> PyResult<Vec<(Vec<usize>, f64)>> {
+        self.rank_by_preference(py, &numbers, preferences, target, top_n.unwrap_or(10), multiplicative.unwrap_or(false), false)
+    }
+
+    /// 解的数量超过max_solutions时按selection决定保留哪些，结果可复现：
+    /// - "search_order"（默认）：与find_subsets的回溯相同，保留最先找到的
+    /// - "index_order"：按索引列表（升序）的字典序保留最靠前的
+    /// - "fewest_elements"：元素最少的优先，个数相同时按字典序
+    /// - "random"：按种子（set_seed，未设置时为固定值）随机抽样，种子相同时结果相同
+    ///
+    /// 除search_order外需要枚举全部解，但只用有界堆保存max_solutions个。始终使用回溯算法，数值相同的组合只计一次
+    #[pyo3(text_signature = "(numbers, target, max_solutions=10, selection=\"search_order\")")]
+    pub fn find_subsets_selected(&self, py: Python, numbers: Vec<i64>, target: i64, max_solutions: Option<usize>, selection: Option<&str>) -> PyResult<Vec<Vec<usize>>> {
+        let max_solutions = max_solutions.unwrap_or(10);
+        let selection = selection.unwrap_or("search_order");
+        let scorer = match selection.to_ascii_lowercase().as_str() {
+            "search_order" => None,
+            "index_order" => Some(Scorer::IndexOrder),
+            "fewest_elements" => Some(Scorer::FewestElements),
+            "random" => Some(Scorer::Random { seed: self.seed.unwrap_or(0x5EED) }),
+            _ => return Err(SolverError::UnsupportedOption { option: "selection", value: selection.to_string() }.into()),
+        };
+        
+        self.reset_progress(numbers.len());
+        let Some(scorer) = scorer else {
+            let request = SearchRequest::new(target, target, max_solutions);
+            return Ok(Self::strip_sums(self.run_interruptible(py, || self.run_backtracking(&numbers, &request))?));
+        };
+        let collector = SolutionCollector {
+            ranked: Some(RankedSolutions::new(scorer, max_solutions)),
+            ..SolutionCollector::default()
+        };
+        let request = SearchRequest::new(target, target, usize::MAX);
+        let collector = self.run_interruptible(py, || self.run_backtracking_into(&numbers, &request, collector))?;
+        let ranked = collector.ranked.map_or(Ok(Vec::new()), RankedSolutions::into_sorted)?;
+        Ok(ranked.into_iter().map(|(solution, _)| solution).collect())
+    }
+
+    /// 按解中数值的分布对和恰好为target的子集评分，返回得分最高的top_n个 [(索引列表, 得分), ...]
+    ///
+    /// scorer为内置评分方式的名称，或接受所选数值列表、返回float的Python函数（得分越高越好）：
+    /// - "min_variance"（默认）：元素大小越均衡越好，得分为方差的相反数
+    /// - "max_min_element"：最小的元素越大越好，得分为最小值
+    /// - "min_max_element"：最大的元素越小越好，得分为最大值的相反数
+    ///
+    /// 内置评分不需要GIL；Python函数对每个解调用一次并持有GIL，出错时停止搜索并抛出该异常。
+    /// 数值相同的组合只评分一次
+    #[pyo3(text_signature = "(numbers, target, scorer=\"min_variance\", top_n=10)")]
+    pub fn find_subsets_by_score(&self, py: Python, numbers: Vec<i64>, target: i64, scorer: Option<&PyAny>, top_n: Option<usize>) -> PyResult<Vec<(Vec<usize>, f64)>> {
+        let scorer = match scorer {
+            None => Scorer::MinVariance { values: numbers.clone() },
+            Some(scorer) if scorer.is_callable() => Scorer::Callback { values: numbers.clone(), callback: scorer.into() },
+            Some(scorer) => Scorer::builtin(scorer.extract()?, numbers.clone())?,
+        };
+        
+        self.reset_progress(numbers.len());
+        let collector = SolutionCollector {
+            ranked: Some(RankedSolutions::new(scorer, top_n.unwrap_or(10))),
+            ..SolutionCollector::default()
+        };
+        let request = SearchRequest::new(target, target, usize::MAX);
+        let collector = py.allow_threads(|| self.run_backtracking_into(&numbers, &request, collector));
+        collector.ranked.map_or(Ok(Vec::new()), RankedSolutions::into_sorted)
+    }
+}
+
+impl SubsetSumSolver {
+    /// find_subsets_by_preference与find_subsets_knapsack的实现
+    ///
+    /// all_positions为false时相同数值只保留偏好最高的元素组合，为true时每个索引组合都参与排名
+    #[allow(clippy::too_many_arguments)]
+    fn rank_by_preference(&self, py: Python, numbers: &[i64], preferences: Vec<f64>, target: i64, top_n: usize, multiplicative: bool, all_positions: bool) -> PyResult<Vec<(Vec<usize>, f64)>> {
+        if preferences.len() != numbers.len() {
+            return Err(SolverError::LengthMismatch { numbers: numbers.len(), other: preferences.len() }.into());
+        }
+        if preferences.iter().any(|p| !p.is_finite()) {
+            return Err(SolverError::NonFiniteInput.into());
+        }
+        
+        self.reset_progress(numbers.len());
+        let scorer = Scorer::Preference { preferences: preferences.clone(), multiplicative };
+        let collector = SolutionCollector {
+            ranked: Some(RankedSolutions::new(scorer, top_n)),
+            ..SolutionCollector::default()
+        };
+        let request = SearchRequest {
+            preferences: &preferences,
+            all_positions,
+            ..SearchRequest::new(target, target, usize::MAX)
+        };
+        let collector = py.allow_threads(|| self.run_backtracking_into(numbers, &request, collector));
+        collector.ranked.map_or(Ok(Vec::new()), RankedSolutions::into_sorted)
+    }
+    
+    /// find_subset_indices的实现：和恰好为target、不合并相同数值的解
+    ///
+    /// 回溯的预处理只保留正数，含负数或0时改用有符号回溯，选入0的组合同样是独立的解
+    fn all_position_subsets(&self, numbers: &[i64], target: i64, max_solutions: usize) -> Vec<Vec<usize>> {
+        self.reset_progress(numbers.len());
+        if numbers.iter().any(|&x| x <= 0) {
+            self.record_strategy("signed_backtracking");
+            return self.signed_backtracking(numbers, target, target, max_solutions);
+        }
+        let request = SearchRequest {
+            all_positions: true,
+            ..SearchRequest::new(target, target, max_solutions)
+        };
+        let mut solutions = Self::strip_sums(self.run_backtracking(numbers, &request));
+        solutions.iter_mut().for_each(|solution| solution.sort_unstable());
+        solutions
+    }
+    
+    /// 查找子集，根据问题规模和特征自动选择最合适的算法
+    pub fn find_subsets_int(&self, numbers: &[i64], target: i64, max_solutions: usize) -> Vec<Vec<usize>> {
+        self.solve_int(numbers, target, max_solutions, 0)
+    }
+    
+    /// find_subsets_int的实现，expected_solutions为回溯搜索解容器的预分配提示（0表示不预分配）
+    fn solve_int(&self, numbers: &[i64], target: i64, max_solutions: usize, expected_solutions: usize) -> Vec<Vec<usize>> {
+        self.solve_window(numbers, target, target, max_solutions, expected_solutions)
+    }
+    
+    /// 查找和落在 [lower, upper] 内的子集（精确求解即 lower == upper），与find_subsets_int使用相同的算法选择
+    ///
+    /// 位运算与动态规划按区间判断命中；精确求解时解不足会补充离目标最近的组合，
+    /// 区间查询（lower < upper）只返回区间内的解。动态规划每个和只有一个解，区间内每个可达的和各给出一个
+    fn solve_window(&self, numbers: &[i64], lower: i64, upper: i64, max_solutions: usize, expected_solutions: usize) -> Vec<Vec<usize>> {
+        // 重置进度计数器
+        self.reset_progress(numbers.len());
+        
+        // 输入全为非负数时负目标不可达（子集和至少为0），直接返回，不启动搜索也不补充近似解
+        if upper < 0 && numbers.iter().all(|&x| x >= 0) {
+            self.record_strategy("infeasible");
+            return Vec::new();
+        }
+        
+        // 预扫描：落在区间内的单个元素是现成的解，O(n)即可全部给出
+        let singles = self.single_element_matches(numbers, lower, upper, max_solutions);
+        if singles.len() >= max_solutions {
+            self.record_strategy("single_element");
+            return singles;
+        }
+        
+        // 使用问题分析功能选择最佳算法；动态规划表按区间上界建立
+        let algorithm = self.select_algorithm(numbers, upper);
+        self.record_strategy(algorithm.name());
+        
+        // 根据选择的算法执行相应的求解方法
+        let solutions = match algorithm {
+            Algorithm::BitManipulation => {
+                self.find_subsets_with_bit(numbers, lower, upper, max_solutions)
+            },
+            Algorithm::DynamicProgramming => {
+                self.find_subsets_with_dp(numbers, lower, upper, max_solutions)
+            },
+            // 回溯的剪枝与预处理假设输入全为正数，含负数时改用按后缀可达范围剪枝的有符号回溯
+            Algorithm::BacktrackingCompact if numbers.iter().any(|&x| x < 0) => {
+                self.record_strategy("signed_backtracking");
+                self.signed_backtracking(numbers, lower, upper, max_solutions)
+            }
+            Algorithm::BacktrackingCompact => {
+                let request = SearchRequest {
+                    expected_solutions,
+                    ..SearchRequest::new(lower, upper, max_solutions)
+                };
+                Self::strip_sums(self.run_backtracking(numbers, &request))
+            }
+        };
+        
+        // 单元素解排在最前，去掉算法结果中与之重复的解
+        let mut results = singles;
+        let found = results.len();
+        for solution in solutions {
+            if results.len() >= max_solutions {
+                break;
+            }
+            if !results[..found].contains(&solution) {
+                results.push(solution);
+            }
+        }
+        results
+    }
+    
+    /// 记录本次求解实际使用的策略，供get_last_strategy查询
+    fn record_strategy(&self, name: &'static str) {
+        *lock(&self.last_strategy) = name;
+    }
+    
+    /// 找出所有落在 [lower, upper] 内的单个元素，按索引升序，最多max_solutions个
+    ///
+    /// 合并重复值时数值相同的组合只返回一次，因此只取第一个
+    fn single_element_matches(&self, numbers: &[i64], lower: i64, upper: i64, max_solutions: usize) -> Vec<Vec<usize>> {
+        if !self.size_allowed(1) || !self.subset_allowed(1, numbers.len()) {
+            return Vec::new();
+        }
+        let limit = if self.merge_duplicates { max_solutions.min(1) } else { max_solutions };
+        numbers.iter()
+            .enumerate()
+            .filter(|&(_, &x)| x >= lower && x <= upper)
+            .map(|(i, _)| vec![i])
+            .take(limit)
+            .collect()
+    }
+    
+    /// 在问题分析的基础上考虑求解器设置，确定实际使用的算法
+    ///
+    /// 动态规划与回溯只保留正数（负数会让“超过目标就剪枝”失效），位运算按带符号的和枚举全部组合，
+    /// 因此输入含负数且规模允许时优先使用位运算，否则使用回溯（实际走有符号回溯），不受阈值和合并重复值设置影响。
+    /// 合并重复值只在回溯算法中实现；动态规划无法保证元素个数限制，设置了限制时改用回溯
+    fn select_algorithm(&self, numbers: &[i64], target: i64) -> Algorithm {
+        // 动态规划只处理正数；含负数时小规模用位运算，否则用回溯（find_subsets_int中改走有符号回溯）
+        if numbers.iter().any(|&x| x < 0) {
+            return if numbers.len() <= MAX_BITMASK_N { Algorithm::BitManipulation } else { Algorithm::BacktrackingCompact };
+        }
+        if self.merge_duplicates {
+            return Algorithm::BacktrackingCompact;
+        }
+        match self.analyze_problem(numbers, target) {
+            Algorithm::DynamicProgramming if self.has_size_limits() => Algorithm::BacktrackingCompact,
+            // 目标超出usize（32位/WASM）或DP表超出内存限制时不能使用动态规划
+            Algorithm::DynamicProgramming if self.dp_table_bytes(target).is_none_or(|bytes| bytes > self.memory_tracker.max_memory) => {
+                Algorithm::BacktrackingCompact
+            }
+            algorithm => algorithm,
+        }
+    }
+    
+    /// find_subsets_with_dp的表需要的字节数，目标为负或在当前平台上放不下时为None
+    fn dp_table_bytes(&self, target: i64) -> Option<usize> {
+        usize::try_from(target.max(0)).ok()?
+            .checked_add(1)?
+            .checked_mul(std::mem::size_of::<bool>() + std::mem::size_of::<Vec<usize>>())
+    }
+    
+    /// 估算实际使用的算法在最坏情况下的工作量：位运算为2^n个组合，动态规划为n*(target+1)个格子，
+    /// 回溯为2^n个节点（剪枝后通常远小于此值）
+    fn estimate_work(&self, numbers: &[i64], target: i64) -> (Algorithm, u128) {
+        let algorithm = self.select_algorithm(numbers, target);
+        let work = match algorithm {
+            Algorithm::DynamicProgramming => numbers.len() as u128 * (target.max(0) as u128 + 1),
+            Algorithm::BitManipulation | Algorithm::BacktrackingCompact => subset_count_u128(numbers.len()),
+        };
+        (algorithm, work)
+    }
+    
+    /// 估算求解需要由内存追踪器登记的字节数
+    fn estimate_memory_bytes(&self, numbers: &[i64], target: i64, max_solutions: usize) -> usize {
+        match self.select_algorithm(numbers, target) {
+            Algorithm::DynamicProgramming => self.dp_table_bytes(target).unwrap_or(usize::MAX),
+            Algorithm::BitManipulation | Algorithm::BacktrackingCompact => {
+                // 解的数量不会超过子集总数
+                let subsets = usize::try_from(subset_count(numbers.len())).unwrap_or(usize::MAX);
+                let per_solution = std::mem::size_of::<(Vec<usize>, i64)>() + numbers.len() * std::mem::size_of::<usize>();
+                max_solutions.min(subsets).saturating_mul(per_solution)
+            }
+        }
+    }
+    
+    /// 查找子集，启用缓存时先查询缓存，未命中时求解并写入缓存
+    fn find_subsets_cached(&self, numbers: &[i64], target: i64, max_solutions: usize, expected_solutions: usize) -> Vec<Vec<usize>> {
+        if lock(&self.cache).is_none() {
+            return self.solve_int(numbers, target, max_solutions, expected_solutions);
+        }
+        
+        let key = self.cache_key(numbers, target, max_solutions);
+        if let Some(solutions) = lock(&self.cache).as_mut().and_then(|c| c.get(key, numbers, target, max_solutions)) {
+            self.record_strategy("cache");
+            return solutions;
+        }
+        
+        let solutions = self.solve_int(numbers, target, max_solutions, expected_solutions);
+        // 只缓存自然结束的搜索：被停止、超出内存限额或部分解已交给解回调的结果不完整
+        if !self.cancelled.load(Ordering::SeqCst) && !self.truncated.load(Ordering::SeqCst) {
+            if let Some(cache) = lock(&self.cache).as_mut() {
+                cache.insert(key, numbers, target, max_solutions, &solutions);
+            }
+        }
+        solutions
+    }
+    
+    /// 计算缓存键：输入、目标值、最大解数量以及所有影响结果的设置
+    ///
+    /// 新增影响求解结果的设置时需要同步加入此处
+    fn cache_key(&self, numbers: &[i64], target: i64, max_solutions: usize) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        numbers.hash(&mut hasher);
+        target.hash(&mut hasher);
+        max_solutions.hash(&mut hasher);
+        self.seed.hash(&mut hasher);
+        self.min_len.hash(&mut hasher);
+        self.max_len.hash(&mut hasher);
+        self.merge_duplicates.hash(&mut hasher);
+        let t = self.thresholds;
+        (t.bitmask_max_n, t.dp_max_n, t.dp_max_target).hash(&mut hasher);
+        (self.allow_empty, self.allow_full).hash(&mut hasher);
+        self.memory_tracker.max_memory.hash(&mut hasher);
+        hasher.finish()
+    }
+    
+    /// 复制求解器的设置（不含回调、缓存和进度状态），用于在后台线程上独立求解
+    fn clone_settings(&self) -> SubsetSumSolver {
+        let mut solver = SubsetSumSolver::new();
+        solver.memory_tracker.max_memory = self.memory_tracker.max_memory;
+        solver.memory_tracker.ordering = self.memory_tracker.ordering;
+        solver.seed = self.seed;
+        solver.min_len = self.min_len;
+        solver.max_len = self.max_len;
+        solver.merge_duplicates = self.merge_duplicates;
+        solver.thresholds = self.thresholds;
+        solver.int_width = self.int_width;
+        solver.search_stack_size = self.search_stack_size;
+        solver.collect_prune_stats = self.collect_prune_stats;
+        solver.allow_empty = self.allow_empty;
+        solver.allow_full = self.allow_full;
+        solver.partial_on_interrupt = self.partial_on_interrupt;
+        solver.dp_block_words = self.dp_block_words;
+        solver.dp_prefetch_words = self.dp_prefetch_words;
+        solver
+    }
+    
+    /// 在调用线程上释放GIL求解，求解期间各算法检查停止标志时每隔INTERRUPT_CHECK_INTERVAL检查一次信号
+    ///
+    /// 收到KeyboardInterrupt等信号异常后停止搜索（结果不进入缓存），然后按partial_on_interrupt
+    /// 抛出该异常或返回已找到的解；求解中的panic转换为INTERNAL_ERROR异常。
+    /// 求解不离开调用线程，不产生创建线程的开销，线程局部的CompactSubset对象池也保持复用
+    fn run_interruptible<T: Send>(&self, py: Python, solve: impl FnOnce() -> T + Send) -> PyResult<T> {
+        self.interrupt.start();
+        let result = py.allow_threads(|| std::panic::catch_unwind(std::panic::AssertUnwindSafe(solve)));
+        let interrupt = self.interrupt.finish(py);
+        let result = result.map_err(|panic| SolverError::SolverPanic { message: panic_message(panic.as_ref()) })?;
+        self.take_callback_error()?;
+        match interrupt {
+            Some(err) if !self.partial_on_interrupt => Err(err),
+            _ => Ok(result),
+        }
+    }
+    
+    /// 取出求解期间解回调抛出的异常
+    fn take_callback_error(&self) -> PyResult<()> {
+        lock(&self.callback_error).take().map_or(Ok(()), Err)
+    }
+    
+    /// 检查是否应该停止；可中断求解期间顺带检查信号，收到中断时设置停止和取消标志
+    #[inline]
+    fn stopped(&self) -> bool {
+        if self.interrupt.poll() {
+            self.stop_execution();
+        }
+        self.stop_flag.load(Ordering::SeqCst) || deadline_expired()
+    }
+    
+    /// 重置进度计数器和停止标志
+    fn reset_progress(&self, n: usize) {
+        self.processed_combinations.store(0, Ordering::SeqCst);
+        self.total_combinations.store(subset_count(n), Ordering::SeqCst);
+        self.stop_flag.store(false, Ordering::SeqCst);
+        self.cancelled.store(false, Ordering::SeqCst);
+        self.truncated.store(false, Ordering::SeqCst);
+        *lock(&self.callback_error) = None;
+        self.prune_stats.reset();
+        self.max_depth.store(0, Ordering::SeqCst);
+        *lock(&self.solve_started) = Some(Instant::now());
+    }
+    
+    /// 去掉解附带的和，只保留索引列表
+    fn strip_sums(solutions: Vec<(Vec<usize>, i64)>) -> Vec<Vec<usize>> {
+        solutions.into_iter().map(|(solution, _)| solution).collect()
+    }
+    
+    /// 使用回溯算法查找和落在 [lower, upper] 内的子集，精确匹配即 lower == upper
+    ///
+    /// 返回每个解及其实际的和
+    fn run_backtracking(&self, numbers: &[i64], request: &SearchRequest) -> Vec<(Vec<usize>, i64)> {
+        let collector = self.run_backtracking_into(numbers, request, SolutionCollector::default());
+        
+        // 结果交还给调用者，不再由内存追踪器统计
+        self.memory_tracker.deallocate(collector.tracked_bytes);
+        collector.solutions
+    }
+    
+    /// 回溯搜索的核心流程，把解记录到给定的收集器中并返回它
+    fn run_backtracking_into(&self, numbers: &[i64], request: &SearchRequest, mut collector: SolutionCollector) -> SolutionCollector {
+        self.record_strategy(Algorithm::BacktrackingCompact.name());
+        // 整除约束把区间收紧到最近的倍数：剪枝按收紧后的区间进行，区间内没有倍数时无解
+        let divisor = request.divisible_by.unwrap_or(1);
+        debug_assert!(divisor > 0, "除数必须为正");
+        let lower = (request.lower as i128).div_euclid(divisor as i128) * divisor as i128
+            + if (request.lower as i128).rem_euclid(divisor as i128) == 0 { 0 } else { divisor as i128 };
+        let upper = (request.upper as i128).div_euclid(divisor as i128) * divisor as i128;
+        if lower > upper {
+            return collector;
+        }
+        // lower <= upper 时两者都在 [request.lower, request.upper] 内，不会溢出
+        let (lower, upper) = (lower as i64, upper as i64);
+        if request.distinct_sums && collector.seen_sums.is_none() {
+            collector.seen_sums = Some(HashSet::new());
+        }
+        // 预分配的空间与解一起登记，由释放解的调用者一并释放；超出内存限额时不预分配
+        let reserve = request.expected_solutions.min(request.max_solutions).saturating_sub(collector.solutions.capacity());
+        let reserve_bytes = reserve.saturating_mul(std::mem::size_of::<(Vec<usize>, i64)>());
+        if reserve > 0 && self.memory_tracker.allocate(reserve_bytes) {
+            collector.solutions.reserve_exact(reserve);
+            collector.tracked_bytes += reserve_bytes;
+        }
+        
+        // 创建线程安全的解决方案容器
+        let solutions = Mutex::new(collector);
+        
+        // 预处理数据；锚点（或偏好高的索引）在相同数值中排在前面，去重时保留它们的选择
+        // 时间窗按元素而不是按数值设置，相同数值不可互换，因此不合并分组；FIFO模式按索引搜索，也不合并
+        let merge = (self.merge_duplicates || request.canonical) && request.windows.is_empty() && !request.fifo && !request.all_positions;
+        let tie_ranks = request.tie_ranks();
+        let (sorted_numbers, sorted_indices, prefix_sum, groups, anchor_flags) = if merge {
+            let groups = group_duplicates(numbers, &tie_ranks);
+            let values: Vec<i64> = groups.iter().map(|g| g.value).collect();
+            let totals: Vec<i64> = groups.iter().map(|g| g.value * g.indices.len() as i64).collect();
+            let prefix_sum = Self::compute_prefix_sum_simd(&totals);
+            let anchor_flags: Vec<bool> = if request.anchors.is_empty() {
+                Vec::new()
+            } else {
+                groups.iter().map(|g| request.anchors[g.indices[0]]).collect()
+            };
+            (values, Vec::new(), prefix_sum, groups, anchor_flags)
+        } else {
+            let (sorted_numbers, sorted_indices, prefix_sum) = self.preprocess_data(numbers, &tie_ranks, request.fifo);
+            let anchor_flags: Vec<bool> = if request.anchors.is_empty() {
+                Vec::new()
+            } else {
+                sorted_indices.iter().map(|&i| request.anchors[i]).collect()
+            };
+            (sorted_numbers, sorted_indices, prefix_sum, Vec::new(), anchor_flags)
+        };
+        let anchor_suffix = suffix_counts(&anchor_flags);
+        let windows: Vec<(i64, i64)> = if request.windows.is_empty() {
+            Vec::new()
+        } else {
+            sorted_indices.iter().map(|&i| request.windows[i]).collect()
+        };
+        
+        let ctx = SearchContext {
+            source: numbers,
+            numbers: &sorted_numbers,
+            indices: &sorted_indices,
+            groups: &groups,
+            prefix_sum: &prefix_sum,
+            lower,
+            upper,
+            minimal_only: request.minimal_only,
+            min_len: self.min_len,
+            max_len: self.max_len.unwrap_or(usize::MAX),
+            allow_empty: self.allow_empty,
+            allow_full: self.allow_full,
+            anchor_suffix: &anchor_suffix,
+            windows: &windows,
+            descending: !request.fifo,
+            all_positions: request.all_positions,
+            divisor,
+            prune_stats: self.collect_prune_stats.then_some(&self.prune_stats),
+            max_depth: &self.max_depth,
+            solutions: &solutions,
+            max_solutions: request.max_solutions,
+            should_stop: &self.stop_flag,
+            truncated: &self.truncated,
+            memory: &self.memory_tracker,
+            solution_callback: self.solution_callback.as_ref(),
+            callback_error: &self.callback_error,
+        };
+        
+        let search = || {
+            if merge {
+                let mut counts = vec![0; groups.len()];
+                self.backtracking_with_counts(&ctx, 0, 0, 0, false, &mut counts);
+            } else {
+                // 创建当前子集实例
+                let mut current_subset = get_compact_subset_from_pool();
                 
-                // 如果达到最大解数量，提前结束
-                if sols.len() >= max_solutions {
-                    should_stop.store(true, Ordering::SeqCst);
+                // 调用回溯算法的核心实现
+                self.backtracking_with_compact_subset(&ctx, 0, 0, false, (i64::MIN, i64::MAX), &mut current_subset);
+                
+                // 归还对象到池
+                return_compact_subset_to_pool(current_subset);
+            }
+        };
+        
+        // 递归深度最多为元素（或分组）个数，过深时在栈更大的线程上搜索，避免调用线程栈溢出。
+        // 信号只能在主线程上检查，调用线程等待期间继续检查中断
+        let depth = if merge { groups.len() } else { sorted_numbers.len() };
+        if depth > DEEP_RECURSION_THRESHOLD {
+            std::thread::scope(|scope| {
+                let (done_tx, done_rx) = std::sync::mpsc::channel::<()>();
+                // 截止时间是线程局部的，交给搜索线程，结束后把是否超时带回调用线程
+                let deadline = DEADLINE.with(Cell::get);
+                let spawned = std::thread::Builder::new()
+                    .name("subset-sum-search".into())
+                    .stack_size(self.search_stack_size)
+                    .spawn_scoped(scope, move || {
+                        DEADLINE.with(|cell| cell.set(deadline));
+                        search();
+                        drop(done_tx);
+                        DEADLINE.with(Cell::get)
+                    });
+                match spawned {
+                    Ok(handle) => {
+                        while let Err(RecvTimeoutError::Timeout) = done_rx.recv_timeout(INTERRUPT_CHECK_INTERVAL) {
+                            if self.interrupt.check_now() {
+                                self.stop_execution();
+                            }
+                        }
+                        match handle.join() {
+                            Ok(deadline) => DEADLINE.with(|cell| cell.set(deadline)),
+                            Err(panic) => std::panic::resume_unwind(panic),
+                        }
+                    }
+                    // 无法创建线程时退回当前线程搜索
+                    Err(_) => search(),
                 }
+            });
+        } else {
+            search();
+        }
+        
+        solutions.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+    
+    /// 是否设置了解的元素个数限制
+    fn has_size_limits(&self) -> bool {
+        self.min_len > 0 || self.max_len.is_some()
+    }
+    
+    /// 检查元素个数是否满足限制
+    fn size_allowed(&self, len: usize) -> bool {
+        len >= self.min_len && self.max_len.is_none_or(|max_len| len <= max_len)
+    }
+    
+    /// 检查元素个数为len的子集是否因空集/全集设置被排除，n为输入元素总数
+    fn subset_allowed(&self, len: usize, n: usize) -> bool {
+        (len > 0 || self.allow_empty) && (len < n || self.allow_full)
+    }
+    
+    /// 分析问题特征，选择最合适的算法
+    fn analyze_problem(&self, numbers: &[i64], target: i64) -> Algorithm {
+        let n = numbers.len();
+        
+        // 基于问题规模的初步判断
+        let thresholds = self.thresholds;
+        if n <= thresholds.bitmask_max_n {
+            // 小规模问题，适合位运算
+            return Algorithm::BitManipulation;
+        } else if n <= thresholds.dp_max_n && target <= thresholds.dp_max_target {
+            // 中等规模问题，目标和不太大，适合动态规划
+            return Algorithm::DynamicProgramming;
+        }
+        
+        // 对于更大规模问题，进一步分析数据特征
+        
+        // 计算数据统计特征
+        let mut sum = 0i64;
+        let mut min_val = i64::MAX;
+        let mut max_val = i64::MIN;
+        let mut positive_count = 0;
+        
+        for &num in numbers {
+            if num > 0 {
+                sum += num;
+                min_val = min_val.min(num);
+                max_val = max_val.max(num);
+                positive_count += 1;
+            }
+        }
+        
+        let _avg = if positive_count > 0 { sum / positive_count } else { 0 };
+        let range = max_val - min_val;
+        
+        // 如果所有数都很小，或者值范围很窄，动态规划可能表现更好
+        if (max_val <= 100 || range <= 100) && target <= 10000 && n <= 200 {
+            return Algorithm::DynamicProgramming;
+        }
+        
+        // 如果数字比较大，但数量不太多，也可以使用动态规划
+        if n <= 150 && target <= 50000 {
+            return Algorithm::DynamicProgramming;
+        }
+        
+        // 默认使用内存优化的回溯算法
+        Algorithm::BacktrackingCompact
+    }
+    
+    /// 预处理数据，优化搜索效率
+    ///
+    /// 相同数值按tie_rank从小到大排列（如锚点、偏好高的索引在前），为空表示无优先。
+    /// keep_index_order为true时不排序，保持原始索引顺序（FIFO模式）
+    fn preprocess_data(&self, numbers: &[i64], tie_rank: &[usize], keep_index_order: bool) -> (Vec<i64>, Vec<usize>, Vec<i64>) {
+        // 过滤负数和零，只保留正数
+        let mut filtered: Vec<(usize, i64)> = numbers.iter()
+            .enumerate()
+            .filter(|&(_, &x)| x > 0)
+            .map(|(i, &x)| (i, x))
+            .collect();
+
+        // 按值降序排序，有助于更快找到解；相同数值的先后由种子决定，未设置种子时按原始索引
+        let rank = |i: usize| tie_rank.get(i).copied().unwrap_or(0);
+        match self.seed {
+            _ if keep_index_order => {}
+            Some(seed) => {
+                let mut rng = SplitMix64::new(seed);
+                let mut keyed: Vec<(usize, i64, u64)> = filtered.iter()
+                    .map(|&(i, x)| (i, x, rng.next_u64()))
+                    .collect();
+                keyed.sort_unstable_by(|a, b| {
+                    b.1.cmp(&a.1)
+                        .then(rank(a.0).cmp(&rank(b.0)))
+                        .then(a.2.cmp(&b.2))
+                });
+                filtered = keyed.into_iter().map(|(i, x, _)| (i, x)).collect();
             }
+            None => filtered.sort_by_key(|&(i, x)| (std::cmp::Reverse(x), rank(i))),
+        }
+
+        // 分离索引和值
+        let sorted_indices: Vec<usize> = filtered.iter().map(|&(i, _)| i).collect();
+        let sorted_numbers: Vec<i64> = filtered.iter().map(|&(_, v)| v).collect();
+
+        // 计算前缀和，用于剪枝
+        let prefix_sum = Self::compute_prefix_sum_simd(&sorted_numbers);
+
+        (sorted_numbers, sorted_indices, prefix_sum)
+    }
+    
+    /// 回溯算法（带紧凑子集表示）
+    ///
+    /// anchored表示当前子集是否已包含锚点（未设置锚点时不使用）
+    fn backtracking_with_compact_subset(
+        &self,
+        ctx: &SearchContext,
+        start: usize,
+        current_sum: i64,
+        anchored: bool,
+        window: (i64, i64),
+        current_subset: &mut CompactSubset,
+    ) {
+        let numbers = ctx.numbers;
+        
+        // 检查是否应该停止
+        if self.stopped() {
             return;
         }
+        ctx.reached(current_subset.len());
+
+        // 剪枝：即使选上所有剩余数字，元素个数也达不到下限
+        if current_subset.len() + (numbers.len() - start) < ctx.min_len {
+            ctx.pruned(PruneRule::EndOfList);
+            return;
+        }
+        
+        // 剪枝：还没有选中锚点，剩余数字中也没有锚点
+        let has_anchors = !ctx.anchor_suffix.is_empty();
+        if has_anchors && !anchored && ctx.anchor_suffix[start] == 0 {
+            return;
+        }
+
+        // 找到一个解
+        if current_sum >= ctx.lower && current_sum <= ctx.upper {
+            // 数字降序排列时，最后选入的numbers[start - 1]是子集中最小的元素；按索引排列时需要逐个查找
+            let is_minimal = || {
+                let smallest = if ctx.descending {
+                    numbers[start - 1]
+                } else {
+                    current_subset.to_indices().into_iter().map(|idx| numbers[idx]).min().unwrap_or(0)
+                };
+                current_sum - smallest < ctx.lower
+            };
+            let anchor_ok = !has_anchors || anchored;
+            if current_subset.len() >= ctx.min_len && anchor_ok && (!ctx.minimal_only || start == 0 || is_minimal()) {
+                // 将紧凑表示转换回索引列表
+                let solution = current_subset.to_indices()
+                    .into_iter()
+                    .map(|idx| ctx.indices[idx])
+                    .collect();
+                Self::record_solution(ctx, solution, current_sum);
+            }
+            // 和已达上限时继续选数只会超出区间；只要极小解时，包含当前子集的超集都不是极小解
+            if current_sum == ctx.upper || ctx.minimal_only {
+                return;
+            }
+        }
 
-        // 剪枝：如果当前和已经超过目标，或剩余数字加起来也不够，提前结束
-        if current_sum > target {
+        // 剪枝：如果当前和已经超过上限，或已达到元素个数上限，提前结束
+        if current_sum > ctx.upper {
+            ctx.pruned(PruneRule::OverTarget);
+            return;
+        }
+        if current_subset.len() >= ctx.max_len {
+            ctx.pruned(PruneRule::EndOfList);
             return;
         }
 
-        // 剪枝：检查剩余数字能否达到目标
-        let remaining_sum = Self::range_sum_simd(prefix_sum, start, numbers.len());
-        if current_sum + remaining_sum < target {
+        // 剪枝：检查剩余数字能否达到下限（数字降序排列，最多还能选的个数取最大的那几个；
+        // 按索引排列时最大的几个不在开头，只能用剩余数字的总和作为上界）
+        let slots = if ctx.descending { ctx.max_len - current_subset.len() } else { usize::MAX };
+        let remaining_sum = Self::range_sum_simd(ctx.prefix_sum, start, start.saturating_add(slots));
+        if current_sum + remaining_sum < ctx.lower {
+            ctx.pruned(PruneRule::SumBound);
             return;
         }
 
-        // 更新进度计数器
-        let processed = 1u64 << start;
+        // 更新进度计数器；2^start饱和时不计入，避免计数器回绕
+        let processed = if start < u64::BITS as usize { subset_count(start) } else { 0 };
         self.processed_combinations.fetch_add(processed, Ordering::SeqCst);
 
         // 考虑当前数字，然后递归
         for i in start..numbers.len() {
-            // 剪枝：跳过重复值
-            if i > start && numbers[i] == numbers[i - 1] {
+            // 剪枝：选入numbers[i]后，剩余数字全选也达不到元素个数下限
+            if current_subset.len() + (numbers.len() - i) < ctx.min_len {
+                ctx.pruned(PruneRule::EndOfList);
+                break;
+            }
+            
+            // 剪枝：跳过重复值（设置时间窗时，时间窗也相同才可互换）；
+            // 按索引排列时相同数值不相邻，只有前面同值的元素都已选中时才选它，即每个数值总是选最靠前的几个
+            let repeated = if ctx.all_positions {
+                false
+            } else if ctx.descending {
+                i > start && numbers[i] == numbers[i - 1] && ctx.windows.get(i) == ctx.windows.get(i - 1)
+            } else {
+                (0..i).any(|j| numbers[j] == numbers[i] && !current_subset.contains(j))
+            };
+            if repeated {
+                continue;
+            }
+            
+            // 剪枝：选入后时间窗的公共部分为空
+            let new_window = match ctx.windows.get(i) {
+                Some(&(from, to)) => (window.0.max(from), window.1.min(to)),
+                None => window,
+            };
+            if new_window.0 > new_window.1 {
                 continue;
             }
 
             let new_sum = current_sum + numbers[i];
-            if new_sum <= target {
+            if new_sum <= ctx.upper {
+                let is_anchor = has_anchors && ctx.anchor_suffix[i] > ctx.anchor_suffix[i + 1];
                 current_subset.add(i);
-                self.backtracking_with_compact_subset(
-                    numbers,
-                    indices,
-                    prefix_sum,
-                    target,
-                    i + 1,
-                    new_sum,
-                    current_subset,
-                    solutions,
-                    max_solutions,
-                    should_stop
-                );
+                self.backtracking_with_compact_subset(ctx, i + 1, new_sum, anchored || is_anchor, new_window, current_subset);
                 current_subset.remove(i);
                 
                 // 检查是否应该停止
-                if should_stop.load(Ordering::SeqCst) {
+                if self.stopped() {
                     return;
                 }
+            } else {
+                ctx.pruned(PruneRule::OverTarget);
+            }
+        }
+    }
+    
+    /// 记录一个解，达到最大解数量时通知所有分支停止
+    ///
+    /// 解占用的内存登记在内存追踪器中。超出内存限制时，如果设置了解回调，
+    /// 则把已收集的解整批交给回调并清空，继续搜索；否则停止搜索
+    fn record_solution(ctx: &SearchContext, solution: Vec<usize>, sum: i64) {
+        debug_assert_solution(ctx.source, &solution, sum, sum);
+        debug_assert!(sum >= ctx.lower && sum <= ctx.upper, "解的和 {} 不在 [{}, {}] 内", sum, ctx.lower, ctx.upper);
+        let len = solution.len();
+        if (len == 0 && !ctx.allow_empty) || (len == ctx.source.len() && !ctx.allow_full) {
+            return;
+        }
+        if sum % ctx.divisor != 0 {
+            return;
+        }
+        
+        let mut collector = lock(ctx.solutions);
+        if let Some(misses) = collector.near_misses.as_mut() {
+            if sum != misses.target {
+                misses.add(solution, sum);
+                return;
+            }
+        }
+        if collector.total() >= ctx.max_solutions {
+            return;
+        }
+        
+        // 每个和只保留一个代表解；区间内所有的和都出现过后不必继续搜索
+        if let Some(seen) = collector.seen_sums.as_mut() {
+            if !seen.insert(sum) {
+                return;
+            }
+            if seen.len() as u128 > ((ctx.upper as i128 - ctx.lower as i128) / ctx.divisor as i128) as u128 {
+                ctx.should_stop.store(true, Ordering::SeqCst);
+            }
+        }
+        
+        if let Some(frequency) = collector.frequency.as_mut() {
+            for &i in &solution {
+                frequency[i] += 1;
+            }
+        }
+        if let Some(stats) = collector.stats.as_mut() {
+            stats.add(len, sum);
+            if collector.total() >= ctx.max_solutions {
+                ctx.should_stop.store(true, Ordering::SeqCst);
+            }
+            return;
+        }
+        
+        if let Some(ranked) = collector.ranked.as_mut() {
+            if !ranked.add(solution) {
+                ctx.should_stop.store(true, Ordering::SeqCst);
+            }
+            return;
+        }
+        
+        // 通道满时在此阻塞，搜索随之暂停；接收端关闭时停止搜索
+        if let Some(sink) = collector.sink.as_mut() {
+            if sink.skip > 0 {
+                sink.skip -= 1;
+            } else if sink.sender.send(solution).is_err() {
+                ctx.should_stop.store(true, Ordering::SeqCst);
+            }
+            return;
+        }
+        
+        let bytes = solution_bytes(&solution);
+        if !ctx.memory.allocate(bytes) {
+            // 无论是交给回调还是停止搜索，返回的解都不再是完整的结果
+            ctx.truncated.store(true, Ordering::SeqCst);
+            let flushed = match ctx.solution_callback {
+                Some(callback) if !collector.solutions.is_empty() => Self::flush_solutions(ctx, &mut collector, callback),
+                _ => false,
+            };
+            if !flushed || !ctx.memory.allocate(bytes) {
+                ctx.should_stop.store(true, Ordering::SeqCst);
+                return;
+            }
+        }
+        collector.tracked_bytes += bytes;
+        collector.solutions.push((solution, sum));
+        
+        // 如果达到最大解数量，提前结束
+        if collector.total() >= ctx.max_solutions {
+            ctx.should_stop.store(true, Ordering::SeqCst);
+        }
+    }
+    
+    /// 把已收集的解整批交给回调 callback([(索引列表, 和), ...]) 并释放其内存
+    ///
+    /// 回调出错时返回false，记录第一个异常，由求解入口抛出
+    fn flush_solutions(ctx: &SearchContext, collector: &mut SolutionCollector, callback: &PyObject) -> bool {
+        let chunk = std::mem::take(&mut collector.solutions);
+        ctx.memory.deallocate(collector.tracked_bytes);
+        collector.tracked_bytes = 0;
+        collector.flushed += chunk.len();
+        
+        match Python::with_gil(|py| callback.call1(py, (chunk,))) {
+            Ok(_) => true,
+            Err(err) => {
+                lock(ctx.callback_error).get_or_insert(err);
+                false
+            }
+        }
+    }
+    
+    /// 按分组个数回溯：第group组选取counts[group]个，相同数值的排列只搜索一次
+    ///
+    /// 锚点在组内排在最前，因此含锚点的分组只要选取至少一个就选中了锚点
+    fn backtracking_with_counts(
+        &self,
+        ctx: &SearchContext,
+        group: usize,
+        current_sum: i64,
+        current_len: usize,
+        anchored: bool,
+        counts: &mut Vec<usize>,
+    ) {
+        if self.stopped() {
+            return;
+        }
+        ctx.reached(group);
+        
+        // 剪枝：还没有选中锚点，剩余分组中也没有锚点
+        let has_anchors = !ctx.anchor_suffix.is_empty();
+        if has_anchors && !anchored && ctx.anchor_suffix[group] == 0 {
+            return;
+        }
+        
+        // 剪枝：剩余分组全部选上也达不到下限
+        let remaining_sum = Self::range_sum_simd(ctx.prefix_sum, group, ctx.groups.len());
+        let remaining_len: usize = ctx.groups[group..].iter().map(|g| g.indices.len()).sum();
+        if current_sum + remaining_sum < ctx.lower {
+            ctx.pruned(PruneRule::SumBound);
+            return;
+        }
+        if current_len + remaining_len < ctx.min_len {
+            ctx.pruned(PruneRule::EndOfList);
+            return;
+        }
+        
+        if group == ctx.groups.len() {
+            // 分组降序排列，最后一个选中的分组数值即子集中最小的元素
+            let smallest = (0..group).rev().find(|&g| counts[g] > 0).map_or(0, |g| ctx.groups[g].value);
+            let is_minimal = current_len == 0 || current_sum - smallest < ctx.lower;
+            if current_sum <= ctx.upper && (!has_anchors || anchored) && (!ctx.minimal_only || is_minimal) {
+                let solution = ctx.groups.iter()
+                    .zip(counts.iter())
+                    .flat_map(|(g, &count)| g.indices[..count].iter().copied())
+                    .collect();
+                Self::record_solution(ctx, solution, current_sum);
+            }
+            return;
+        }
+        
+        self.processed_combinations.fetch_add(1, Ordering::SeqCst);
+        
+        // 先尝试选得多的情况，与逐个元素降序搜索的顺序一致
+        let value = ctx.groups[group].value;
+        let max_count = ctx.groups[group].indices.len().min(ctx.max_len - current_len);
+        for count in (0..=max_count).rev() {
+            let new_sum = current_sum + value * count as i64;
+            if new_sum > ctx.upper {
+                ctx.pruned(PruneRule::OverTarget);
+                continue;
+            }
+            let picks_anchor = count > 0 && has_anchors && ctx.anchor_suffix[group] > ctx.anchor_suffix[group + 1];
+            counts[group] = count;
+            self.backtracking_with_counts(ctx, group + 1, new_sum, current_len + count, anchored || picks_anchor, counts);
+            counts[group] = 0;
+            
+            if self.stopped() {
+                return;
             }
         }
     }
     
     /// 范围求和函数
-    fn evaluate_branch(&self, numbers: &[i64], prefix_sum: &[i64], from: usize, to: usize) -> i64 {
+    #[allow(dead_code)]
+    fn evaluate_branch(&self, _numbers: &[i64], prefix_sum: &[i64], from: usize, to: usize) -> i64 {
         if from >= to {
             return 0;
         }
         Self::range_sum_simd(prefix_sum, from, to)
     }
     
-    /// 并行回溯算法的阈值确定
-    fn should_parallelize(&self, depth: usize, numbers: &[i64]) -> bool {
-        let parallel_threshold = 16; // 使用固定阈值简化实现
-        depth <= parallel_threshold && numbers.len() >= 16
-    }
-    
     /// 使用位运算算法求解子集和问题
     /// 这种方法在小规模问题(数量不超过32个)上非常高效
-    fn find_subsets_with_bit(&self, numbers: &[i64], target: i64, max_solutions: usize) -> Vec<Vec<usize>> {
+    ///
+    /// 该路径不对输入排序：mask的第i位（最低位为第0位）始终对应原始的numbers[i]，
+    /// 求和（masked_sum_i64及SIMD实现）与mask_to_indices都依赖这一约定。
+    /// 如果以后在这里引入preprocess_data之类的重排，必须先把位映射回原始索引
+    ///
+    /// 和落在 [lower, upper] 内即为命中；精确求解（lower == upper）命中不足max_solutions时补充离目标最近的组合，
+    /// 区间查询不补充，结果都在区间内
+    fn find_subsets_with_bit(&self, numbers: &[i64], lower: i64, upper: i64, max_solutions: usize) -> Vec<Vec<usize>> {
         // 如果数字数量超过了位运算的限制，切换到其他算法
-        if numbers.len() > 32 {
-            return self.find_subsets_with_dp(numbers, target, max_solutions);
+        if numbers.len() > MAX_BITMASK_N {
+            self.record_strategy(Algorithm::DynamicProgramming.name());
+            return self.find_subsets_with_dp(numbers, lower, upper, max_solutions);
         }
         
+        // 解先以mask保存（n不超过MAX_BITMASK_N，一个u64即可表示），返回前再统一转换为索引列表，
+        // 避免每个精确解和候选近似解都单独分配Vec
         let n = numbers.len();
-        let mut results = Vec::new();
-        let mut best_diff = i64::MAX;
-        let mut best_candidates = Vec::new();
+        let pad = lower == upper;
+        let mut results: Vec<u64> = Vec::new();
+        let mut best_diff = i128::MAX;
+        let mut best_candidates: Vec<u64> = Vec::new();
+        
+        // i32路径内存带宽减半且每个AVX2寄存器处理8个数；i128路径保证极大数值的子集和不溢出
+        let width = self.int_width.resolve(numbers, lower).max(self.int_width.resolve(numbers, upper));
+        let narrow: Option<Vec<i32>> = (width == IntWidth::I32)
+            .then(|| numbers.iter().map(|&x| x as i32).collect());
+        let masked_sum_i32 = select_masked_sum_i32();
         
         // 计算所有2^n种组合
-        let total_combinations = 1 << n;
+        debug_assert!(n <= MAX_BITMASK_N);
+        let total_combinations = subset_count(n);
         
-        for mask in 1..total_combinations {
-            let mut sum = 0;
+        let first_mask = if self.allow_empty { 0 } else { 1 };
+        for mask in first_mask..total_combinations {
+            // 跳过元素个数不满足限制的组合
+            let len = mask.count_ones() as usize;
+            if !self.size_allowed(len) || !self.subset_allowed(len, n) {
+                continue;
+            }
             
-            // 计算当前组合的和
-            for i in 0..n {
-                if (mask & (1 << i)) != 0 {
-                    sum += numbers[i];
+            // 计算当前组合的和，统一放宽到i128比较
+            let sum: i128 = match (&narrow, width) {
+                (Some(values), _) => {
+                    let sum = masked_sum_i32(values, mask) as i128;
+                    debug_assert_eq!(sum, Self::masked_sum_i128(numbers, mask));
+                    sum
                 }
-            }
+                (None, IntWidth::I128) => Self::masked_sum_i128(numbers, mask),
+                (None, _) => Self::masked_sum_i64(numbers, mask) as i128,
+            };
             
             // 更新进度
             self.processed_combinations.fetch_add(1, Ordering::SeqCst);
             
             // 检查是否应该停止
-            if self.stop_flag.load(Ordering::SeqCst) {
+            if self.stopped() {
                 break;
             }
             
-            // 如果找到精确匹配
-            if sum == target {
-                // 构建解决方案
-                let mut solution = Vec::new();
-                for i in 0..n {
-                    if (mask & (1 << i)) != 0 {
-                        solution.push(i);
-                    }
-                }
-                
-                results.push(solution);
+            // 如果找到精确匹配（和落在区间内）
+            if sum >= lower as i128 && sum <= upper as i128 {
+                debug_assert_solution(numbers, &Self::mask_to_indices(mask, n), lower, upper);
+                results.push(mask);
                 
                 // 如果达到最大解数量，提前结束
                 if results.len() >= max_solutions {
                     break;
                 }
             } 
-            // 如果没有足够的精确匹配，记录接近的组合
-            else if results.len() < max_solutions {
-                let diff = (sum - target).abs();
+            // 精确求解时如果没有足够的精确匹配，记录接近的组合
+            else if pad && results.len() < max_solutions {
+                let diff = if sum < lower as i128 { lower as i128 - sum } else { sum - upper as i128 };
                 
                 if diff < best_diff {
                     best_diff = diff;
                     best_candidates.clear();
                     
-                    let mut candidate = Vec::new();
-                    for i in 0..n {
-                        if (mask & (1 << i)) != 0 {
-                            candidate.push(i);
-                        }
-                    }
-                    best_candidates.push(candidate);
+                    best_candidates.push(mask);
                 } 
                 else if diff == best_diff && best_candidates.len() < max_solutions - results.len() {
-                    let mut candidate = Vec::new();
-                    for i in 0..n {
-                        if (mask & (1 << i)) != 0 {
-                            candidate.push(i);
-                        }
-                    }
-                    best_candidates.push(candidate);
+                    best_candidates.push(mask);
                 }
             }
         }
@@ -538,45 +3822,614 @@ impl SubsetSumSolver {
             results.extend(best_candidates);
         }
         
-        // 确保返回的结果不超过max_solutions
-        if results.len() > max_solutions {
-            results.truncate(max_solutions);
+        // 确保返回的结果不超过max_solutions
+        if results.len() > max_solutions {
+            results.truncate(max_solutions);
+        }
+        
+        results.into_iter().map(|mask| Self::mask_to_indices(mask, n)).collect()
+    }
+    
+    /// 计算mask选中元素之和（i64标准实现）
+    fn masked_sum_i64(numbers: &[i64], mask: u64) -> i64 {
+        numbers.iter()
+            .enumerate()
+            .filter(|&(i, _)| mask & (1 << i) != 0)
+            .map(|(_, &num)| num)
+            .sum()
+    }
+    
+    /// 计算mask选中元素之和（i128实现，任意输入都不会溢出）
+    fn masked_sum_i128(numbers: &[i64], mask: u64) -> i128 {
+        numbers.iter()
+            .enumerate()
+            .filter(|&(i, _)| mask & (1 << i) != 0)
+            .map(|(_, &num)| num as i128)
+            .sum()
+    }
+    
+    /// 将mask转换为升序的原始索引列表：第i位为1表示选中numbers[i]
+    fn mask_to_indices(mask: u64, n: usize) -> Vec<usize> {
+        debug_assert!(n >= 64 || mask >> n == 0, "mask包含超出数字个数的位");
+        (0..n).filter(|&i| mask & (1 << i) != 0).collect()
+    }
+    
+    /// 从每组恰好选一个数使和为target，返回各组所选的组内索引
+    fn one_per_group(&self, groups: &[Vec<i64>], target: i64, max_solutions: usize) -> Vec<Vec<usize>> {
+        if groups.iter().any(|g| g.is_empty()) {
+            return Vec::new();
+        }
+        // 组内按数值升序排列（保留组内索引），便于提前结束本组的枚举
+        let sorted: Vec<Vec<(i64, usize)>> = groups.iter()
+            .map(|g| {
+                let mut options: Vec<(i64, usize)> = g.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+                options.sort_unstable();
+                options
+            })
+            .collect();
+        // suffix_min[g]/suffix_max[g]：第g组及之后各组最小值/最大值之和
+        let k = sorted.len();
+        let mut suffix_min = vec![0i128; k + 1];
+        let mut suffix_max = vec![0i128; k + 1];
+        for g in (0..k).rev() {
+            suffix_min[g] = suffix_min[g + 1] + sorted[g][0].0 as i128;
+            suffix_max[g] = suffix_max[g + 1] + sorted[g][sorted[g].len() - 1].0 as i128;
+        }
+        
+        let mut results = Vec::new();
+        let mut tracked = 0;
+        let mut choice = Vec::with_capacity(k);
+        self.one_per_group_from(&sorted, &suffix_min, &suffix_max, target as i128, 0, 0, &mut choice, &mut results, &mut tracked, max_solutions);
+        self.memory_tracker.deallocate(tracked);
+        results
+    }
+    
+    /// 含负数输入的回溯：每个元素选或不选，按剩余元素可达的和的范围剪枝，查找和落在 [lower, upper] 内的子集
+    ///
+    /// 元素按绝对值降序搜索，剩余元素可达的和位于 [当前和 + 剩余负数之和, 当前和 + 剩余正数之和] 内，
+    /// 与目标区间不相交时回退。不合并相同数值，每个索引组合都是独立的解（包括是否选入0）；解为升序的原始索引
+    fn signed_backtracking(&self, numbers: &[i64], lower: i64, upper: i64, max_solutions: usize) -> Vec<Vec<usize>> {
+        let mut order: Vec<usize> = (0..numbers.len()).collect();
+        order.sort_by_key(|&i| (std::cmp::Reverse(numbers[i].unsigned_abs()), i));
+        // suffix_neg[j]/suffix_pos[j]：order[j..]中负数/正数之和
+        let mut suffix_neg = vec![0i128; order.len() + 1];
+        let mut suffix_pos = vec![0i128; order.len() + 1];
+        for j in (0..order.len()).rev() {
+            let value = numbers[order[j]] as i128;
+            suffix_neg[j] = suffix_neg[j + 1] + value.min(0);
+            suffix_pos[j] = suffix_pos[j + 1] + value.max(0);
+        }
+        
+        let mut results = Vec::new();
+        let mut tracked = 0;
+        let mut chosen = Vec::new();
+        let window = (lower as i128, upper as i128);
+        self.signed_backtracking_from(numbers, &order, &suffix_neg, &suffix_pos, window, 0, 0, &mut chosen, &mut results, &mut tracked, max_solutions);
+        self.memory_tracker.deallocate(tracked);
+        results
+    }
+    
+    /// signed_backtracking的递归部分：已决定order[..pos]，选中的为chosen，当前和为sum，window为 (lower, upper)
+    #[allow(clippy::too_many_arguments)]
+    fn signed_backtracking_from(&self, numbers: &[i64], order: &[usize], suffix_neg: &[i128], suffix_pos: &[i128], window: (i128, i128),
+                                pos: usize, sum: i128, chosen: &mut Vec<usize>, results: &mut Vec<Vec<usize>>, tracked: &mut usize, max_solutions: usize) {
+        if results.len() >= max_solutions || self.stopped() {
+            return;
+        }
+        self.processed_combinations.fetch_add(1, Ordering::SeqCst);
+        let (lower, upper) = window;
+        if sum + suffix_neg[pos] > upper || sum + suffix_pos[pos] < lower {
+            return;
+        }
+        let len = chosen.len();
+        if pos == order.len() {
+            if self.size_allowed(len) && self.subset_allowed(len, numbers.len()) {
+                let mut solution = chosen.clone();
+                solution.sort_unstable();
+                let bytes = solution_bytes(&solution);
+                if !self.memory_tracker.allocate(bytes) {
+                    self.truncated.store(true, Ordering::SeqCst);
+                    self.stop_flag.store(true, Ordering::SeqCst);
+                    return;
+                }
+                *tracked += bytes;
+                debug_assert_solution(numbers, &solution, lower as i64, upper as i64);
+                results.push(solution);
+            }
+            return;
+        }
+        if self.max_len.is_none_or(|max_len| len < max_len) {
+            chosen.push(order[pos]);
+            self.signed_backtracking_from(numbers, order, suffix_neg, suffix_pos, window, pos + 1, sum + numbers[order[pos]] as i128, chosen, results, tracked, max_solutions);
+            chosen.pop();
+        }
+        if len + (order.len() - pos - 1) >= self.min_len {
+            self.signed_backtracking_from(numbers, order, suffix_neg, suffix_pos, window, pos + 1, sum, chosen, results, tracked, max_solutions);
+        }
+    }
+    
+    /// one_per_group的回溯部分：已为前g组选好choice，当前和为sum
+    #[allow(clippy::too_many_arguments)]
+    fn one_per_group_from(&self, sorted: &[Vec<(i64, usize)>], suffix_min: &[i128], suffix_max: &[i128], target: i128,
+                          g: usize, sum: i128, choice: &mut Vec<usize>, results: &mut Vec<Vec<usize>>, tracked: &mut usize, max_solutions: usize) {
+        if results.len() >= max_solutions || self.stopped() {
+            return;
+        }
+        self.processed_combinations.fetch_add(1, Ordering::SeqCst);
+        if g == sorted.len() {
+            if sum == target {
+                let bytes = solution_bytes(choice);
+                if !self.memory_tracker.allocate(bytes) {
+                    self.stop_flag.store(true, Ordering::SeqCst);
+                    return;
+                }
+                *tracked += bytes;
+                results.push(choice.clone());
+            }
+            return;
+        }
+        for &(value, index) in &sorted[g] {
+            let next = sum + value as i128;
+            // 升序枚举：剩余组取最小值也超过目标时，本组更大的值同样超过
+            if next + suffix_min[g + 1] > target {
+                break;
+            }
+            if next + suffix_max[g + 1] < target {
+                continue;
+            }
+            choice.push(index);
+            self.one_per_group_from(sorted, suffix_min, suffix_max, target, g + 1, next, choice, results, tracked, max_solutions);
+            choice.pop();
+        }
+    }
+    
+    /// 最优优先搜索和落在 [low, high] 内的子集，按和与preferred的距离升序返回（距离相同时先找到的在前）
+    ///
+    /// 节点的优先级是它可能达到的最小距离：可达的和位于 [当前和, 当前和 + 剩余之和] 与区间的交集内，
+    /// 交集为空时剪枝；完整子集的优先级就是它的实际距离，因此出队顺序即结果顺序，无需事后排序。
+    /// 搜索节点保存在arena中，每个节点只记录父节点和新选的元素，避免为每个节点复制子集；
+    /// 每展开一个节点登记两个节点的内存，超出内存限制或被停止时返回已找到的部分
+    fn best_first_range(&self, numbers: &[i64], low: i64, high: i64, preferred: i64, max_solutions: usize) -> Vec<(Vec<usize>, i64)> {
+        const NO_PARENT: usize = usize::MAX;
+        // arena节点：(父节点, 选中的排序后位置)
+        let node_bytes = std::mem::size_of::<(usize, usize)>() + std::mem::size_of::<(i128, i64, usize, usize, usize, usize)>();
+        
+        let (sorted_numbers, sorted_indices, prefix_sum) = self.preprocess_data(numbers, &[], false);
+        let n = sorted_numbers.len();
+        let mut arena: Vec<(usize, usize)> = Vec::new();
+        let mut tracked = 0usize;
+        
+        // 队列元素：(逆最小距离, 当前和, 逆序号, 下一个位置, 已选个数, arena节点)，距离相同时先入队的先出
+        let mut heap = BinaryHeap::new();
+        let mut seq = 0usize;
+        let distance = |sum: i64, next: usize| -> Option<i128> {
+            let reachable_low = (sum as i128).max(low as i128);
+            let reachable_high = (sum as i128 + Self::range_sum_simd(&prefix_sum, next, n) as i128).min(high as i128);
+            (reachable_low <= reachable_high)
+                .then(|| (reachable_low - preferred as i128).max(preferred as i128 - reachable_high).max(0))
+        };
+        if let Some(d) = distance(0, 0) {
+            heap.push((std::cmp::Reverse(d), 0i64, std::cmp::Reverse(seq), 0usize, 0usize, NO_PARENT));
+        }
+        
+        let mut results = Vec::new();
+        while let Some((_, sum, _, next, len, node)) = heap.pop() {
+            if results.len() >= max_solutions || self.stopped() {
+                break;
+            }
+            self.processed_combinations.fetch_add(1, Ordering::SeqCst);
+            
+            if next == n {
+                if self.size_allowed(len) && self.subset_allowed(len, numbers.len()) {
+                    let mut solution = Vec::with_capacity(len);
+                    let mut cursor = node;
+                    while cursor != NO_PARENT {
+                        let (parent, pos) = arena[cursor];
+                        solution.push(sorted_indices[pos]);
+                        cursor = parent;
+                    }
+                    solution.sort_unstable();
+                    debug_assert_solution(numbers, &solution, sum, sum);
+                    results.push((solution, sum));
+                }
+                continue;
+            }
+            
+            // 剪枝：即使选上所有剩余数字，元素个数也达不到下限
+            if len + (n - next) < self.min_len {
+                continue;
+            }
+            
+            if !self.memory_tracker.allocate(2 * node_bytes) {
+                break;
+            }
+            tracked += 2 * node_bytes;
+            
+            // 选入sorted_numbers[next]
+            let value = sorted_numbers[next];
+            if self.max_len.is_none_or(|max_len| len < max_len) {
+                if let Some(d) = distance(sum + value, next + 1) {
+                    arena.push((node, next));
+                    seq += 1;
+                    heap.push((std::cmp::Reverse(d), sum + value, std::cmp::Reverse(seq), next + 1, len + 1, arena.len() - 1));
+                }
+            }
+            // 不选
+            if let Some(d) = distance(sum, next + 1) {
+                seq += 1;
+                heap.push((std::cmp::Reverse(d), sum, std::cmp::Reverse(seq), next + 1, len, node));
+            }
+        }
+        
+        self.memory_tracker.deallocate(tracked);
+        results
+    }
+    
+    /// 位集动态规划：bits第s位表示和s可达，每个正数x执行一次整体左移x位再按位或
+    ///
+    /// 从高位字向低位字原地更新，读取的低位字尚未被本轮修改，保证每个数只用一次
+    fn bitset_reachable(&self, numbers: &[i64], target: usize, words: usize) -> bool {
+        let mut bits = vec![0u64; words];
+        bits[0] = 1; // 空集的和为0
+        let (target_word, target_bit) = (target / 64, target % 64);
+        
+        for &num in numbers {
+            self.processed_combinations.fetch_add(1, Ordering::SeqCst);
+            if self.stopped() {
+                return false;
+            }
+            if num <= 0 || num as usize > target {
+                continue;
+            }
+            let (word_shift, bit_shift) = (num as usize / 64, (num % 64) as u32);
+            // 按超级块从高位向低位处理，块内与块间的顺序都和逐字处理一致
+            let block = if self.dp_block_words == 0 { words } else { self.dp_block_words };
+            let mut end = words;
+            while end > word_shift {
+                let start = end.saturating_sub(block).max(word_shift);
+                shift_or_words(&mut bits, word_shift, bit_shift, start..end, self.dp_prefetch_words);
+                end = start;
+                if end > word_shift && self.stopped() {
+                    return false;
+                }
+            }
+            if bits[target_word] >> target_bit & 1 == 1 {
+                return true;
+            }
+        }
+        (bits[target_word] >> target_bit & 1 == 1) && (target > 0 || self.allow_empty)
+    }
+    
+    /// min_breaking_set的一层搜索：能否再删除至多budget个数字使target不可达，成功时removed为删除的索引
+    ///
+    /// 已删除的数字在remaining中置为0（求解时会被过滤），保持原始索引不变。
+    /// 回溯找到一个解后会设置stop_flag，这里随即清除；用户的停止请求通过cancelled判断
+    fn break_within(&self, remaining: &mut [i64], target: i64, words: usize, budget: usize, removed: &mut Vec<usize>) -> bool {
+        if self.cancelled.load(Ordering::SeqCst) {
+            return false;
+        }
+        if !self.bitset_reachable(remaining, target as usize, words) {
+            return !self.cancelled.load(Ordering::SeqCst);
+        }
+        let witness = self.run_backtracking(remaining, &SearchRequest::new(target, target, 1)).into_iter().next();
+        if self.cancelled.load(Ordering::SeqCst) {
+            return false;
+        }
+        self.stop_flag.store(false, Ordering::SeqCst);
+        let Some((witness, _)) = witness else {
+            return true; // 元素个数限制下无解
+        };
+        if budget == 0 {
+            return false;
+        }
+        for i in witness {
+            let value = std::mem::replace(&mut remaining[i], 0);
+            removed.push(i);
+            if self.break_within(remaining, target, words, budget - 1, removed) {
+                remaining[i] = value;
+                return true;
+            }
+            removed.pop();
+            remaining[i] = value;
+        }
+        false
+    }
+    
+    /// 分桶分配：返回每个数所在的桶，使每个桶的和落在 [lows[k], highs[k]] 内
+    fn assign_buckets(&self, numbers: &[i64], lows: &[i64], highs: &[i64]) -> Option<Vec<usize>> {
+        let k = lows.len();
+        // 桶的和不会为负；回溯只在放入时检查上限，因此要求初始的空桶不超出上限
+        if k == 0 || highs.iter().any(|&high| high < 0) {
+            return (k == 0 && numbers.is_empty()).then(Vec::new);
+        }
+        let mut order: Vec<usize> = (0..numbers.len()).collect();
+        order.sort_by_key(|&i| (std::cmp::Reverse(numbers[i]), i));
+        let fits = |sums: &[i64]| (0..k).all(|b| sums[b] >= lows[b] && sums[b] <= highs[b]);
+        
+        // 贪心：每个数放入离目标（区间中点）差得最多的桶
+        self.record_strategy("greedy");
+        let mut sums = vec![0i64; k];
+        let mut assignment = vec![0; numbers.len()];
+        for &i in &order {
+            let bucket = (0..k)
+                .max_by_key(|&b| ((lows[b] as i128 + highs[b] as i128) / 2 - sums[b] as i128, std::cmp::Reverse(b)))
+                .unwrap();
+            sums[bucket] += numbers[i];
+            assignment[i] = bucket;
+        }
+        if fits(&sums) {
+            return Some(assignment);
+        }
+        
+        // 回溯：suffix[j]为order[j..]的和
+        self.record_strategy(Algorithm::BacktrackingCompact.name());
+        let mut suffix = vec![0i64; order.len() + 1];
+        for j in (0..order.len()).rev() {
+            suffix[j] = suffix[j + 1] + numbers[order[j]];
+        }
+        sums.iter_mut().for_each(|s| *s = 0);
+        self.assign_buckets_from(numbers, lows, highs, &order, &suffix, 0, &mut sums, &mut assignment)
+            .then_some(assignment)
+    }
+    
+    /// assign_buckets的回溯部分：为order[pos..]分配桶，成功时assignment即为结果
+    #[allow(clippy::too_many_arguments)]
+    fn assign_buckets_from(&self, numbers: &[i64], lows: &[i64], highs: &[i64], order: &[usize], suffix: &[i64],
+                           pos: usize, sums: &mut [i64], assignment: &mut [usize]) -> bool {
+        if self.stopped() {
+            return false;
+        }
+        self.processed_combinations.fetch_add(1, Ordering::SeqCst);
+        // 剪枝：剩余的数必须能补足各桶的缺口，且每个数都要有地方放
+        let (mut deficit, mut room) = (0i128, 0i128);
+        for b in 0..sums.len() {
+            deficit += (lows[b] as i128 - sums[b] as i128).max(0);
+            room += highs[b] as i128 - sums[b] as i128;
+        }
+        let remaining = suffix[pos] as i128;
+        if remaining < deficit || remaining > room {
+            return false;
+        }
+        let Some(&i) = order.get(pos) else {
+            return true;
+        };
+        for b in 0..sums.len() {
+            // 上下限和当前和都相同的桶可以互换，只尝试第一个
+            if (0..b).any(|c| (lows[c], highs[c], sums[c]) == (lows[b], highs[b], sums[b])) {
+                continue;
+            }
+            if sums[b] + numbers[i] > highs[b] {
+                continue;
+            }
+            sums[b] += numbers[i];
+            assignment[i] = b;
+            if self.assign_buckets_from(numbers, lows, highs, order, suffix, pos + 1, sums, assignment) {
+                return true;
+            }
+            sums[b] -= numbers[i];
+        }
+        false
+    }
+    
+    /// 计数动态规划：counts[i * (target+1) + s] 为numbers[i..]中和为s的子集个数（含空集），超出u128时饱和
+    fn suffix_subset_counts(&self, numbers: &[i64], target: usize) -> Vec<u128> {
+        let n = numbers.len();
+        let width = target + 1;
+        let mut counts = vec![0u128; (n + 1) * width];
+        counts[n * width] = 1;
+        for i in (0..n).rev() {
+            self.processed_combinations.fetch_add(1, Ordering::SeqCst);
+            let num = numbers[i] as usize;
+            let (row, next) = counts.split_at_mut((i + 1) * width);
+            let row = &mut row[i * width..];
+            for s in 0..width {
+                row[s] = next[s];
+                if s >= num {
+                    row[s] = row[s].saturating_add(next[s - num]);
+                }
+            }
+        }
+        counts
+    }
+    
+    /// 按字典序求第k个和为target的子集（原始索引升序），counts由suffix_subset_counts给出
+    ///
+    /// 在状态(起点i, 剩余和s)上：剩余和为0时“到此结束”的解排在最前，
+    /// 然后按下一个选取的索引j依次排列，以j开头的解有 counts[j+1][s - numbers[j]] 个，逐块跳过即可。
+    /// 空集不允许时跳过根节点的“到此结束”；全集不允许时先求出它的序号，序号不小于它的k加一
+    fn unrank_subset(numbers: &[i64], target: usize, counts: &[u128], k: u128, allow_empty: bool, allow_full: bool) -> Option<Vec<usize>> {
+        let n = numbers.len();
+        let width = target + 1;
+        let count = |i: usize, s: usize| counts[i * width + s];
+        let ends_here = |depth: usize, s: usize| s == 0 && (depth > 0 || allow_empty);
+        
+        let mut k = k;
+        if !allow_full && numbers.iter().map(|&x| x as u128).sum::<u128>() == target as u128 {
+            // 全集的序号：逐位累加排在它前面的解
+            let mut rank: u128 = 0;
+            let mut s = target;
+            for (depth, &num) in numbers.iter().enumerate() {
+                rank = rank.saturating_add(ends_here(depth, s) as u128);
+                s -= num as usize;
+            }
+            if k >= rank {
+                k = k.checked_add(1)?;
+            }
+        }
+        
+        let mut solution = Vec::new();
+        let mut start = 0;
+        let mut s = target;
+        loop {
+            if ends_here(solution.len(), s) {
+                if k == 0 {
+                    return Some(solution);
+                }
+                k -= 1;
+            }
+            let next = (start..n).find(|&j| {
+                let num = numbers[j] as usize;
+                if num > s {
+                    return false;
+                }
+                let block = count(j + 1, s - num);
+                if k < block {
+                    return true;
+                }
+                k -= block;
+                false
+            })?;
+            solution.push(next);
+            s -= numbers[next] as usize;
+            start = next + 1;
+        }
+    }
+    
+    /// 查找和为target的连续子数组，返回半开区间 (start, end)，按结束位置、再按开始位置升序
+    ///
+    /// 前缀和用i128累加不会溢出；starts[p]记录前缀和为p的所有位置，
+    /// 以end结束的区间的开始位置就是前缀和等于 prefix[end] - target 的那些位置
+    fn contiguous_ranges(&self, numbers: &[i64], target: i64, max_solutions: usize) -> Vec<(usize, usize)> {
+        let n = numbers.len();
+        self.reset_progress(n);
+        self.total_combinations.store(n as u64, Ordering::SeqCst);
+        self.record_strategy("contiguous");
+        
+        let mut starts: HashMap<i128, Vec<usize>> = HashMap::new();
+        let mut prefix: i128 = 0;
+        starts.entry(0).or_default().push(0);
+        let mut ranges = Vec::new();
+        for end in 1..=n {
+            self.processed_combinations.fetch_add(1, Ordering::SeqCst);
+            if self.stopped() {
+                break;
+            }
+            prefix += numbers[end - 1] as i128;
+            if let Some(candidates) = starts.get(&(prefix - target as i128)) {
+                for &start in candidates {
+                    let len = end - start;
+                    if self.size_allowed(len) && self.subset_allowed(len, n) {
+                        ranges.push((start, end));
+                        if ranges.len() >= max_solutions {
+                            return ranges;
+                        }
+                    }
+                }
+            }
+            starts.entry(prefix).or_default().push(end);
+        }
+        ranges
+    }
+    
+    /// 用动态规划求和不超过target且最接近target的子集，返回 (索引列表, 和)
+    ///
+    /// from[s]记录第一次到达和s的元素，到达时s - numbers[i]只用到了更靠前的元素，
+    /// 因此沿from回溯即可还原子集。每处理完一个元素后最优和变大时，以 (索引列表, 和) 调用on_improve，
+    /// 它返回false时停止。内存不足或被停止时返回None
+    fn closest_under_dp(&self, numbers: &[i64], target: i64, mut on_improve: impl FnMut(&[usize], i64) -> bool) -> Option<(Vec<usize>, i64)> {
+        let target_usize = usize::try_from(target.max(0)).ok()?;
+        let memory_size = target_usize.checked_add(1)?.checked_mul(std::mem::size_of::<usize>())?;
+        if !self.memory_tracker.allocate(memory_size) {
+            return None;
+        }
+        
+        self.total_combinations.store(numbers.len() as u64, Ordering::SeqCst);
+        const UNREACHED: usize = usize::MAX;
+        let mut from = vec![UNREACHED; target_usize + 1];
+        let trace = |from: &[usize], best: usize| {
+            let mut solution = Vec::new();
+            let mut s = best;
+            while s > 0 {
+                let idx = from[s];
+                solution.push(idx);
+                s -= numbers[idx] as usize;
+            }
+            solution.sort_unstable();
+            debug_assert_solution(numbers, &solution, best as i64, best as i64);
+            solution
+        };
+        let mut best = 0;
+        let mut stopped = false;
+        'outer: for (idx, &num) in numbers.iter().enumerate() {
+            self.processed_combinations.fetch_add(1, Ordering::SeqCst);
+            if num <= 0 || num as usize > target_usize {
+                continue;
+            }
+            let num = num as usize;
+            let previous_best = best;
+            // 从大到小遍历，保证每个元素只用一次
+            for s in (num..=target_usize).rev() {
+                if (target_usize - s) % STOP_CHECK_INTERVAL == 0 && self.stopped() {
+                    stopped = true;
+                    break 'outer;
+                }
+                if from[s] == UNREACHED && (s == num || from[s - num] != UNREACHED) {
+                    from[s] = idx;
+                    best = best.max(s);
+                }
+            }
+            if best > previous_best && !on_improve(&trace(&from, best), best as i64) {
+                stopped = true;
+                break;
+            }
+            if best == target_usize {
+                break;
+            }
         }
         
-        results
+        let result = (!stopped).then(|| (trace(&from, best), best as i64));
+        self.memory_tracker.deallocate(memory_size);
+        result
     }
     
     /// 使用动态规划算法求解子集和问题
     /// 这种方法在中等规模问题(数量不超过100，目标和较小)上更高效
-    fn find_subsets_with_dp(&self, numbers: &[i64], target: i64, max_solutions: usize) -> Vec<Vec<usize>> {
-        if target <= 0 {
+    ///
+    /// 精确求解（lower == upper）需要多个解时补充和小于目标的最近组合，区间查询只返回区间内的解
+    fn find_subsets_with_dp(&self, numbers: &[i64], lower: i64, upper: i64, max_solutions: usize) -> Vec<Vec<usize>> {
+        let n = numbers.len();
+        if upper <= 0 {
+            // 只考虑正数时，和不超过0的只有空集
+            if lower <= 0 && upper == 0 && self.subset_allowed(0, n) {
+                return vec![Vec::new()];
+            }
             return Vec::new();
         }
+        // 表按区间上界建立；区间内每个可达的和各给出一个解
+        let target = upper;
+        let window_low = lower.max(0) as usize;
         
+        // 先登记内存再分配表：目标超出usize或超出内存限制时不分配，避免截断或OOM
+        let Some(memory_size) = self.dp_table_bytes(target) else {
+            self.truncated.store(true, Ordering::SeqCst);
+            return Vec::new();
+        };
+        if !self.memory_tracker.allocate(memory_size) {
+            self.truncated.store(true, Ordering::SeqCst);
+            return Vec::new(); // 内存不足，返回空结果
+        }
         let target_usize = target as usize;
-        let _n = numbers.len();
+        
+        // 进度按已处理的元素个数计算，替换调用者按2^n设置的分母
+        self.processed_combinations.store(0, Ordering::SeqCst);
+        self.total_combinations.store(n as u64, Ordering::SeqCst);
         
         // 创建动态规划表，dp[i]表示是否存在和为i的子集
         let mut dp = vec![false; target_usize + 1];
         dp[0] = true; // 空集的和为0
         
-        // 记录每个可能和的一个可行子集
+        // 记录每个可能和的一个可行子集：只记录第一次到达该和的路径，后来的其他组合不会覆盖它，
+        // 因此每个和最多给出一个解。路径由更早的和追加当前索引得到，索引直接是原始索引且严格递增
         let mut predecessor = vec![Vec::new(); target_usize + 1];
         
-        // 检查内存使用情况
-        let memory_size = dp.len() * std::mem::size_of::<bool>() +
-                         predecessor.len() * std::mem::size_of::<Vec<usize>>();
-        
-        if !self.memory_tracker.allocate(memory_size) {
-            self.memory_tracker.deallocate(memory_size);
-            return Vec::new(); // 内存不足，返回空结果
-        }
-        
         // 记录所有可能的和
         let mut all_sums = vec![0];
         
         // 动态规划填表
         for (idx, &num) in numbers.iter().enumerate() {
+            // 更新进度
+            self.processed_combinations.fetch_add(1, Ordering::SeqCst);
+            
             if num <= 0 {
                 continue; // 跳过非正数
             }
@@ -586,47 +4439,56 @@ impl SubsetSumSolver {
             // 为避免重复计算，从后向前遍历
             let mut new_sums = Vec::new();
             
-            for &prev_sum in &all_sums {
+            for (k, &prev_sum) in all_sums.iter().enumerate() {
+                // 目标很大时单个元素的内层循环也很长，按固定间隔检查停止标志
+                if k % STOP_CHECK_INTERVAL == 0 && self.stopped() {
+                    break;
+                }
                 let new_sum = prev_sum + num_usize;
                 if new_sum <= target_usize && !dp[new_sum] {
                     dp[new_sum] = true;
                     predecessor[new_sum] = predecessor[prev_sum].clone();
                     predecessor[new_sum].push(idx);
                     new_sums.push(new_sum);
-                    
-                    // 更新进度
-                    self.processed_combinations.fetch_add(1, Ordering::SeqCst);
                 }
             }
             
             all_sums.extend(new_sums);
             
             // 检查是否应该停止
-            if self.stop_flag.load(Ordering::SeqCst) {
+            if self.stopped() {
                 self.memory_tracker.deallocate(memory_size);
                 return Vec::new();
             }
         }
         
-        // 收集结果 - 只返回精确匹配的子集
+        // 收集结果 - 先给出区间内的和（按离区间中点的距离），每个和一个解
         let mut solutions = Vec::new();
-        if dp[target_usize] {
-            solutions.push(predecessor[target_usize].clone());
+        let middle = (window_low + target_usize) / 2;
+        let mut hits: Vec<usize> = (window_low..=target_usize)
+            .filter(|&sum| dp[sum] && self.subset_allowed(predecessor[sum].len(), n))
+            .collect();
+        hits.sort_by_key(|&sum| sum.abs_diff(middle));
+        for sum in hits.into_iter().take(max_solutions) {
+            debug_assert_solution(numbers, &predecessor[sum], lower, upper);
+            solutions.push(predecessor[sum].clone());
         }
         
-        // 查找接近目标值的其他解决方案（如果需要多个解）
-        if max_solutions > 1 {
-            // 按照与目标值的接近程度排序
+        // 精确求解时查找接近目标值的其他解决方案（如果需要多个解）
+        if lower == upper && max_solutions > 1 && max_solutions > solutions.len() {
+            // 区间外的和都小于区间下界
             let mut sums_with_solutions: Vec<(usize, Vec<usize>)> = all_sums.iter()
-                .filter(|&&sum| sum != target_usize && dp[sum]) // 排除已找到的精确解
+                .filter(|&&sum| sum < window_low && dp[sum]) // 排除已找到的区间内的解
+                .filter(|&&sum| self.subset_allowed(predecessor[sum].len(), n))
                 .map(|&sum| (sum, predecessor[sum].clone()))
                 .collect();
             
-            // 按照与目标的接近程度排序
-            sums_with_solutions.sort_by_key(|&(sum, _)| (target_usize as i64 - sum as i64).abs());
+            // 按照与区间的接近程度排序
+            sums_with_solutions.sort_by_key(|&(sum, _)| window_low - sum);
             
             // 添加最接近的解决方案，直到达到max_solutions
-            for (_, solution) in sums_with_solutions.into_iter().take(max_solutions - solutions.len()) {
+            for (sum, solution) in sums_with_solutions.into_iter().take(max_solutions - solutions.len()) {
+                debug_assert_solution(numbers, &solution, sum as i64, sum as i64);
                 solutions.push(solution);
             }
         }
@@ -635,8 +4497,117 @@ impl SubsetSumSolver {
         solutions
     }
     
+    /// find_witness的动态规划：parent[s]为第一次到达和s的元素索引加一（0表示不可达，和0总是可达）
+    ///
+    /// 每个元素从大到小更新各个和，读到的parent[s - x]只来自更早的元素，因此回指链上的索引严格递减、每个元素至多用一次
+    fn dp_witness(&self, numbers: &[i64], target: usize) -> Option<Vec<usize>> {
+        let mut parent = vec![0u32; target + 1];
+        for (idx, &num) in numbers.iter().enumerate() {
+            self.processed_combinations.fetch_add(1, Ordering::SeqCst);
+            if num <= 0 || num as usize > target {
+                continue;
+            }
+            let num = num as usize;
+            for sum in (num..=target).rev() {
+                if sum % STOP_CHECK_INTERVAL == 0 && self.stopped() {
+                    return None;
+                }
+                if parent[sum] == 0 && (sum == num || parent[sum - num] != 0) {
+                    parent[sum] = idx as u32 + 1;
+                }
+            }
+            if parent[target] != 0 {
+                break;
+            }
+        }
+        if parent[target] == 0 {
+            return None;
+        }
+        
+        let mut witness = Vec::new();
+        let mut sum = target;
+        while sum > 0 {
+            let idx = parent[sum] as usize - 1;
+            witness.push(idx);
+            sum -= numbers[idx] as usize;
+        }
+        witness.reverse();
+        debug_assert_solution(numbers, &witness, target as i64, target as i64);
+        Some(witness)
+    }
+    
+    /// 自检的一组随机用例，返回不一致的描述
+    fn self_test_case(rng: &mut SplitMix64) -> Result<(), String> {
+        let n = 1 + (rng.next_u64() % 14) as usize;
+        let numbers: Vec<i64> = (0..n).map(|_| 1 + (rng.next_u64() % 40) as i64).collect();
+        // 大多数目标取某个子集的和，保证有解的用例足够多
+        let target = if rng.next_u64().is_multiple_of(4) {
+            (rng.next_u64() % (numbers.iter().sum::<i64>() as u64 + 5)) as i64
+        } else {
+            let mask = rng.next_u64();
+            numbers.iter().enumerate().filter(|&(i, _)| mask >> i & 1 == 1).map(|(_, &x)| x).sum()
+        };
+        let fail = |strategy: &str| format!("{}: numbers={:?}, target={}", strategy, numbers, target);
+        
+        // 暴力枚举作为参照：与默认设置一致，不含空集
+        let expected: BTreeSet<Vec<usize>> = (1..1u64 << n)
+            .map(|mask| Self::mask_to_indices(mask, n))
+            .filter(|indices| indices.iter().map(|&i| numbers[i]).sum::<i64>() == target)
+            .collect();
+        let exact = |solutions: Vec<Vec<usize>>| -> BTreeSet<Vec<usize>> {
+            solutions.into_iter()
+                .filter(|s| s.iter().map(|&i| numbers[i]).sum::<i64>() == target)
+                .map(|mut s| { s.sort_unstable(); s })
+                .collect()
+        };
+        
+        for width in [IntWidth::I32, IntWidth::I64, IntWidth::I128] {
+            let mut solver = SubsetSumSolver::new();
+            solver.int_width = width;
+            if exact(solver.find_subsets_with_bit(&numbers, target, target, usize::MAX)) != expected {
+                return Err(fail(&format!("bitmask({})", width.name())));
+            }
+        }
+        
+        // 回溯对相同数值去重，每个数值组合只返回一个解，因此按数值比较
+        let values = |solutions: &BTreeSet<Vec<usize>>| -> BTreeSet<Vec<i64>> {
+            solutions.iter()
+                .map(|s| { let mut v: Vec<i64> = s.iter().map(|&i| numbers[i]).collect(); v.sort_unstable(); v })
+                .collect()
+        };
+        let mut solver = SubsetSumSolver::new();
+        let request = SearchRequest::new(target, target, usize::MAX);
+        for (merge, strategy) in [(false, "backtracking"), (true, "backtracking(merged)")] {
+            solver.merge_duplicates = merge;
+            let found = exact(Self::strip_sums(solver.run_backtracking(&numbers, &request)));
+            if found.len() != values(&found).len() || values(&found) != values(&expected) {
+                return Err(fail(strategy));
+            }
+        }
+        
+        let solver = SubsetSumSolver::new();
+        let dp = exact(solver.find_subsets_with_dp(&numbers, target, target, 1));
+        if dp.len() > 1 || dp.is_empty() != expected.is_empty() {
+            return Err(fail("dp"));
+        }
+        let words = target as usize / 64 + 1;
+        let reachable = solver.bitset_reachable(&numbers, target as usize, words);
+        if reachable == expected.is_empty() {
+            return Err(fail("bitset"));
+        }
+        
+        let narrow: Vec<i32> = (0..64).map(|_| (rng.next_u64() % 2001) as i32 - 1000).collect();
+        let mask = rng.next_u64();
+        let len = (rng.next_u64() % 65) as usize;
+        if select_masked_sum_i32()(&narrow[..len], mask) != masked_sum_scalar_i32(&narrow[..len], mask) {
+            return Err(format!("masked_sum: values={:?}, mask={:#x}", &narrow[..len], mask));
+        }
+        Ok(())
+    }
+    
     /// 使用SIMD指令集的快速求和实现
     #[inline]
+    #[allow(dead_code)]
     fn fast_sum(array: &[i64]) -> i64 {
         // 对于小数组，使用标准求和避免SIMD开销
         if array.len() < 16 {
@@ -648,6 +4619,7 @@ impl SubsetSumSolver {
     }
     
     /// 标准求和实现（无SIMD）
+    #[allow(dead_code)]
     fn sum_scalar(array: &[i64]) -> i64 {
         array.iter().sum()
     }
@@ -657,31 +4629,931 @@ impl SubsetSumSolver {
         let len = array.len();
         let mut prefix_sum = vec![0; len + 1];
         
-        for i in 0..len {
-            prefix_sum[i + 1] = prefix_sum[i] + array[i];
+        for (i, &value) in array.iter().enumerate() {
+            prefix_sum[i + 1] = prefix_sum[i] + value;
         }
         
         prefix_sum
     }
     
-    /// 使用SIMD优化的范围求和
+    /// 使用SIMD优化的范围求和：返回原数组 [from, to) 内元素之和
+    ///
+    /// 约定（记n = prefix_sum.len() - 1为元素个数）：
+    /// - prefix_sum非空，from <= to 且 from <= n，违反时debug构建直接panic，release构建返回0
+    /// - to允许超过n，按n截断：剪枝时“最多还能选的个数”可能多于剩余元素个数
+    /// - from == to 或 from == n 时为空区间，返回0
     fn range_sum_simd(prefix_sum: &[i64], from: usize, to: usize) -> i64 {
-        if from >= to || from >= prefix_sum.len() - 1 {
+        debug_assert!(!prefix_sum.is_empty(), "前缀和数组至少包含开头的0");
+        let n = prefix_sum.len().saturating_sub(1);
+        debug_assert!(from <= to, "区间起点 {} 大于终点 {}", from, to);
+        debug_assert!(from <= n, "区间起点 {} 超出元素个数 {}", from, n);
+        let end = to.min(n);
+        if from >= end {
             return 0;
         }
-        let end = to.min(prefix_sum.len() - 1);
         prefix_sum[end] - prefix_sum[from]
     }
 }
 
-/// 获取当前CPU支持的SIMD指令集类型
+/// 判断所有数值的绝对值之和以及目标值都能用i32表示，此时任意子集的和都不会溢出i32
+fn fits_i32(numbers: &[i64], target: i64) -> bool {
+    let total = numbers.iter().fold(0i64, |acc, &x| acc.saturating_add(x.saturating_abs()));
+    total <= i32::MAX as i64 && target.saturating_abs() <= i32::MAX as i64
+}
+
+/// 判断所有数值的绝对值之和不超过i64::MAX，此时任意子集的和都不会溢出i64
+fn fits_i64(numbers: &[i64]) -> bool {
+    numbers.iter()
+        .try_fold(0i64, |acc, &x| acc.checked_add(x.checked_abs()?))
+        .is_some()
+}
+
+/// 计算mask选中元素之和的i32实现，values最多64个
+type MaskedSumI32 = fn(&[i32], u64) -> i32;
+
+/// 根据CPU特性选择i32掩码求和实现，只在循环外检测一次
+///
+/// 启用no_simd特性时不编译任何指令集相关代码，始终使用标量实现
+fn select_masked_sum_i32() -> MaskedSumI32 {
+    #[cfg(all(target_arch = "x86_64", not(feature = "no_simd")))]
+    {
+        if is_x86_feature_detected!("avx2") {
+            return |values, mask| unsafe { masked_sum_avx2_i32(values, mask) };
+        }
+    }
+    masked_sum_scalar_i32
+}
+
+/// i32掩码求和的标准实现
+fn masked_sum_scalar_i32(values: &[i32], mask: u64) -> i32 {
+    values.iter()
+        .enumerate()
+        .filter(|&(i, _)| mask & (1 << i) != 0)
+        .map(|(_, &v)| v)
+        .sum()
+}
+
+/// i32掩码求和的AVX2实现：每8个数一组，用掩码的对应8位生成通道选择掩码后累加
+///
+/// 调用者必须保证CPU支持AVX2
+#[cfg(all(target_arch = "x86_64", not(feature = "no_simd")))]
+#[target_feature(enable = "avx2")]
+unsafe fn masked_sum_avx2_i32(values: &[i32], mask: u64) -> i32 {
+    use std::arch::x86_64::*;
+    
+    let lane_bits = _mm256_setr_epi32(1, 2, 4, 8, 16, 32, 64, 128);
+    let mut acc = _mm256_setzero_si256();
+    let chunks = values.chunks_exact(8);
+    let remainder = chunks.remainder();
+    
+    for (c, chunk) in chunks.enumerate() {
+        let bits = _mm256_set1_epi32(((mask >> (c * 8)) & 0xFF) as i32);
+        let selected = _mm256_cmpeq_epi32(_mm256_and_si256(bits, lane_bits), lane_bits);
+        let v = _mm256_loadu_si256(chunk.as_ptr() as *const __m256i);
+        acc = _mm256_add_epi32(acc, _mm256_and_si256(v, selected));
+    }
+    
+    let mut lanes = [0i32; 8];
+    _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, acc);
+    let base = values.len() - remainder.len();
+    // 恰好64个数时没有余下部分，base为64，不能直接移位
+    lanes.iter().sum::<i32>() + masked_sum_scalar_i32(remainder, mask.checked_shr(base as u32).unwrap_or(0))
+}
+
+/// 提示CPU把value所在的缓存行预取到各级缓存，不影响结果
+///
+/// 启用no_simd特性或非x86_64目标时为空操作
+#[inline]
+fn prefetch_read(value: &u64) {
+    #[cfg(all(target_arch = "x86_64", not(feature = "no_simd")))]
+    unsafe {
+        use std::arch::x86_64::{_mm_prefetch, _MM_HINT_T0};
+        // SSE是x86_64的基础指令集，无需运行时检测
+        _mm_prefetch::<_MM_HINT_T0>(value as *const u64 as *const i8);
+    }
+    #[cfg(not(all(target_arch = "x86_64", not(feature = "no_simd"))))]
+    let _ = value;
+}
+
+/// 获取实际使用的SIMD指令集类型，与select_masked_sum_i32的选择一致
 fn detect_simd_support() -> &'static str {
-    "基础" // 简化实现
+    #[cfg(all(target_arch = "x86_64", not(feature = "no_simd")))]
+    {
+        if is_x86_feature_detected!("avx2") {
+            return "avx2";
+        }
+    }
+    "scalar"
+}
+
+/// 分页读取后台搜索的解，由SubsetSumSolver.paginate创建
+///
+/// 每次next_page取走至多page_size个解；搜索结束且解已取完时返回空列表。
+/// 对象被回收或调用close时停止后台搜索
+#[pyclass]
+pub struct SolutionPager {
+    solver: Arc<SubsetSumSolver>,
+    receiver: Option<Receiver<Vec<usize>>>,
+    page_size: usize,
+    cursor: usize,
+}
+
+#[pymethods]
+impl SolutionPager {
+    /// 取下一页解，等待后台搜索产生足够的解或搜索结束；没有更多解时返回空列表
+    fn next_page(&mut self, py: Python) -> Vec<Vec<usize>> {
+        self.take(py, self.page_size)
+    }
+
+    /// 从上次停下的位置继续搜索，再取至多additional个解（"再给我10个"），不重复之前的工作
+    ///
+    /// 与next_page共用同一个暂停中的后台搜索，只是数量不受page_size限制；搜索结束时返回的解可能不足additional个
+    #[pyo3(text_signature = "($self, additional)")]
+    fn continue_search(&mut self, py: Python, additional: usize) -> Vec<Vec<usize>> {
+        self.take(py, additional)
+    }
+
+    /// 已取走的解的个数（含创建时传入的cursor），可传给paginate从断点继续
+    #[getter]
+    fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// 后台搜索是否已结束且所有解都已取走
+    fn is_exhausted(&self) -> bool {
+        self.receiver.is_none()
+    }
+
+    /// 停止后台搜索并丢弃尚未取走的解
+    fn close(&mut self) {
+        self.solver.stop_flag.store(true, Ordering::SeqCst);
+        self.receiver = None;
+    }
+}
+
+impl SolutionPager {
+    /// 从通道中取至多count个解，等待后台搜索产生它们或搜索结束
+    fn take(&mut self, py: Python, count: usize) -> Vec<Vec<usize>> {
+        let Some(receiver) = self.receiver.take() else {
+            return Vec::new();
+        };
+        let (page, receiver) = py.allow_threads(move || {
+            let mut page = Vec::new();
+            while page.len() < count {
+                match receiver.recv() {
+                    Ok(solution) => page.push(solution),
+                    // 发送端已关闭：搜索结束
+                    Err(_) => return (page, None),
+                }
+            }
+            (page, Some(receiver))
+        });
+        self.receiver = receiver;
+        self.cursor += page.len();
+        page
+    }
+}
+
+impl Drop for SolutionPager {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// 在线可达性判断：数字逐个到达，随时查询已到达的数字中是否有子集的和为target
+///
+/// 维护 [0, max_target] 内可达和的位集，每次push把位集左移该值后按位或，
+/// 查询只读一位，不需要重新扫描历史数据。非正数和大于max_target的数不影响可达性，直接忽略
+#[pyclass]
+pub struct OnlineSolver {
+    bits: Vec<u64>,
+    max_target: usize,
+    pushed: usize,
+}
+
+#[pymethods]
+impl OnlineSolver {
+    /// 创建可回答不超过max_target的目标的求解器，位集占用 max_target/8 字节
+    #[new]
+    fn new(max_target: i64) -> PyResult<Self> {
+        let max_target = usize::try_from(max_target).map_err(|_| SolverError::TargetTooLarge)?;
+        let words = max_target / 64 + 1;
+        let mut bits = Vec::new();
+        bits.try_reserve_exact(words).map_err(|_| SolverError::MemoryLimit { bytes: words.saturating_mul(8) })?;
+        bits.resize(words, 0);
+        bits[0] = 1; // 空集的和为0
+        Ok(OnlineSolver { bits, max_target, pushed: 0 })
+    }
+
+    /// 加入一个新到达的数字，耗时 O(max_target/64)
+    #[pyo3(text_signature = "($self, value)")]
+    fn push(&mut self, py: Python, value: i64) {
+        self.pushed += 1;
+        if value <= 0 || value as u64 > self.max_target as u64 {
+            return;
+        }
+        let (word_shift, bit_shift) = (value as usize / 64, (value % 64) as u32);
+        let words = self.bits.len();
+        let bits = &mut self.bits;
+        py.allow_threads(|| shift_or_words(bits, word_shift, bit_shift, word_shift..words, 0));
+    }
+
+    /// 已到达的数字中是否存在和恰好为target的非空子集；target超出max_target时抛出TARGET_TOO_LARGE错误
+    #[pyo3(text_signature = "($self, target)")]
+    fn is_reachable(&self, target: i64) -> PyResult<bool> {
+        if target <= 0 {
+            return Ok(false);
+        }
+        if target as u64 > self.max_target as u64 {
+            return Err(SolverError::TargetTooLarge.into());
+        }
+        let target = target as usize;
+        Ok(self.bits[target / 64] >> (target % 64) & 1 == 1)
+    }
+
+    /// 已push的数字个数（包括被忽略的）
+    fn __len__(&self) -> usize {
+        self.pushed
+    }
 }
 
 /// Python模块定义
 #[pymodule]
-fn subset_sum(_py: Python, m: &PyModule) -> PyResult<()> {
+fn subset_sum(py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<SubsetSumSolver>()?;
+    m.add_class::<SolutionPager>()?;
+    m.add_class::<OnlineSolver>()?;
+    m.add("SubsetSumError", py.get_type::<SubsetSumError>())?;
+    m.add_function(wrap_pyfunction!(set_language, m)?)?;
+    m.add_function(wrap_pyfunction!(get_language, m)?)?;
+    m.add_function(wrap_pyfunction!(capabilities, m)?)?;
+    m.add("API_VERSION", API_VERSION)?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 测试可执行文件需要自行链接libpython，见build.rs
+    include!(concat!(env!("OUT_DIR"), "/link_python.rs"));
+
+    /// 取出SubsetSumError的错误码（构造异常需要已初始化的解释器）
+    fn error_code(err: PyErr) -> String {
+        pyo3::prepare_freethreaded_python();
+        Python::with_gil(|py| err.value(py).getattr("code").and_then(|code| code.extract()).unwrap())
+    }
+
+    #[test]
+    fn mixed_magnitudes_scale_without_dropping_small_values() {
+        let (numbers, target, decimals) = scale_to_integers(&[1_000_000.0, 0.5, 0.25], 1_000_000.75, RoundingMode::Round, None).unwrap();
+        assert_eq!(decimals, 2);
+        assert_eq!(numbers, vec![100_000_000, 50, 25]);
+        assert_eq!(target, 100_000_075);
+    }
+
+    #[test]
+    fn mixed_magnitudes_reject_values_that_scale_to_zero() {
+        pyo3::prepare_freethreaded_python();
+        let err = scale_to_integers(&[1e6, 1e-12], 1e6, RoundingMode::Round, None).unwrap_err();
+        assert_eq!(error_code(err), "SCALED_TO_ZERO");
+        let err = scale_to_integers(&[1000.0, 0.004], 1000.0, RoundingMode::Round, Some(2)).unwrap_err();
+        assert_eq!(error_code(err), "SCALED_TO_ZERO");
+    }
+
+    #[test]
+    fn huge_max_solutions_on_tiny_input_allocates_little() {
+        let mut solver = SubsetSumSolver::new();
+        let solutions = solver.solve_int(&[1, 2, 3], 3, usize::MAX, 0);
+        assert_eq!(*lock(&solver.last_strategy), "bitmask");
+        assert!(solutions.contains(&vec![2]) && solutions.contains(&vec![0, 1]));
+
+        // 动态规划：表只按目标建立
+        let numbers: Vec<i64> = (1..=30).collect();
+        let solutions = solver.solve_int(&numbers, 10, usize::MAX, 0);
+        assert_eq!(*lock(&solver.last_strategy), "dp");
+        assert!(solutions.iter().any(|s| s.iter().map(|&i| numbers[i]).sum::<i64>() == 10));
+        assert!(solver.memory_tracker.peak_memory.load(Ordering::SeqCst) < 4096);
+
+        // 回溯（合并重复值时总是回溯）：max_solutions和预分配提示都极大，超出内存限额的提示被忽略
+        solver.merge_duplicates = true;
+        let solutions = solver.solve_int(&[1, 2, 3], 3, usize::MAX, usize::MAX);
+        assert_eq!(*lock(&solver.last_strategy), "backtracking");
+        assert_eq!(solutions.len(), 2);
+        assert!(solver.memory_tracker.peak_memory.load(Ordering::SeqCst) < 4096);
+        assert_eq!(solver.memory_tracker.get_used_memory(), 0);
+    }
+
+    #[test]
+    fn two_instances_solve_concurrently_and_independently() {
+        // 各自的输入走不同的算法（回溯与位运算），在两个线程上同时求解多轮
+        let large: Vec<i64> = (0..40).map(|i| 100_003 + 7_919 * i * i).collect();
+        let target: i64 = [1, 5, 9, 20, 33].iter().map(|&i| large[i]).sum();
+        let small: Vec<i64> = vec![3, 34, 4, 12, 5, 2, 9, 7];
+        let expected_large = SubsetSumSolver::new().solve_int(&large, target, 50, 0);
+        let expected_small = SubsetSumSolver::new().solve_int(&small, 9, 50, 0);
+        assert!(!expected_large.is_empty() && !expected_small.is_empty());
+
+        let (first, second) = (SubsetSumSolver::new(), SubsetSumSolver::new());
+        std::thread::scope(|scope| {
+            let a = scope.spawn(|| (0..20).map(|_| first.solve_int(&large, target, 50, 0)).collect::<Vec<_>>());
+            let b = scope.spawn(|| (0..20).map(|_| second.solve_int(&small, 9, 50, 0)).collect::<Vec<_>>());
+            for solutions in a.join().unwrap() {
+                assert_eq!(solutions, expected_large);
+            }
+            for solutions in b.join().unwrap() {
+                assert_eq!(solutions, expected_small);
+            }
+        });
+        for solution in &expected_large {
+            assert_eq!(solution.iter().map(|&i| large[i]).sum::<i64>(), target);
+        }
+        assert_eq!(*lock(&first.last_strategy), "backtracking");
+        assert_eq!(*lock(&second.last_strategy), "bitmask");
+    }
+
+    #[test]
+    fn selected_masked_sum_matches_scalar() {
+        // no_simd构建时选中的就是标量实现：cargo test --features no_simd
+        if cfg!(feature = "no_simd") || !cfg!(target_arch = "x86_64") {
+            assert_eq!(detect_simd_support(), "scalar");
+        }
+        let selected = select_masked_sum_i32();
+        let mut rng = SplitMix64::new(24);
+        for n in 0..=MAX_BITMASK_N {
+            let values: Vec<i32> = (0..n).map(|_| (rng.next_u64() % 2_001) as i32 - 1_000).collect();
+            for _ in 0..50 {
+                let mask = rng.next_u64() & (subset_count(n) - 1);
+                assert_eq!(selected(&values, mask), masked_sum_scalar_i32(&values, mask), "n={} mask={:#x}", n, mask);
+            }
+        }
+    }
+
+    #[test]
+    fn range_sum_clamps_to_the_element_count() {
+        let prefix_sum = SubsetSumSolver::compute_prefix_sum_simd(&[5, 4, 3, 2]);
+        assert_eq!(SubsetSumSolver::range_sum_simd(&prefix_sum, 1, 3), 7);
+        // to超过元素个数时按n截断
+        assert_eq!(SubsetSumSolver::range_sum_simd(&prefix_sum, 1, 100), 9);
+        assert_eq!(SubsetSumSolver::range_sum_simd(&prefix_sum, 0, usize::MAX), 14);
+        // from == to 与 from == n 都是空区间
+        assert_eq!(SubsetSumSolver::range_sum_simd(&prefix_sum, 2, 2), 0);
+        assert_eq!(SubsetSumSolver::range_sum_simd(&prefix_sum, 4, 4), 0);
+        assert_eq!(SubsetSumSolver::range_sum_simd(&prefix_sum, 4, 10), 0);
+        assert_eq!(SubsetSumSolver::range_sum_simd(&[0], 0, 3), 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "大于终点")]
+    fn range_sum_rejects_reversed_range_in_debug() {
+        let prefix_sum = SubsetSumSolver::compute_prefix_sum_simd(&[5, 4, 3, 2]);
+        SubsetSumSolver::range_sum_simd(&prefix_sum, 3, 1);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "超出元素个数")]
+    fn range_sum_rejects_start_past_the_end_in_debug() {
+        let prefix_sum = SubsetSumSolver::compute_prefix_sum_simd(&[5, 4, 3, 2]);
+        SubsetSumSolver::range_sum_simd(&prefix_sum, 5, 6);
+    }
+
+    #[test]
+    fn dp_returns_original_indices() {
+        // 未排序、含0、负数和重复值的输入：跳过的非正数不能让后面的索引错位
+        let numbers = [7, 0, 3, -2, 5, 3, 11, 2];
+        let solver = SubsetSumSolver::new();
+        let solutions = solver.find_subsets_with_dp(&numbers, 10, 10, 1);
+        assert_eq!(solutions.len(), 1);
+        let solution = &solutions[0];
+        assert!(solution.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(solution.iter().all(|&i| numbers[i] > 0));
+        assert_eq!(solution.iter().map(|&i| numbers[i]).sum::<i64>(), 10);
+        // 第一次到达10的路径是 7 + 3
+        assert_eq!(solution, &vec![0, 2]);
+
+        // 补充的近似解同样是升序、指向正数的原始索引
+        for solution in solver.find_subsets_with_dp(&numbers, 10, 10, 10) {
+            assert!(solution.windows(2).all(|pair| pair[0] < pair[1]));
+            assert!(solution.iter().all(|&i| numbers[i] > 0));
+        }
+    }
+
+    /// 只保留和恰好为target的解（位运算与动态规划会补充近似解）
+    fn exact(numbers: &[i64], target: i64, solutions: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+        solutions.into_iter().filter(|s| s.iter().map(|&i| numbers[i]).sum::<i64>() == target).collect()
+    }
+
+    #[test]
+    fn numbers_above_target_are_kept_when_negatives_are_present() {
+        // 10大于目标，但与-3一起构成解，不能被“超过目标就过滤”的预处理丢掉
+        // 合并重复值或限制元素个数时原本走回溯，预处理会丢掉10，只剩 [7]
+        let numbers = [10, -3, 7];
+        let mut solver = SubsetSumSolver::new();
+        for setting in 0..3 {
+            solver.merge_duplicates = setting == 1;
+            solver.max_len = (setting == 2).then_some(2);
+            let mut solutions = exact(&numbers, 7, solver.solve_int(&numbers, 7, 10, 0));
+            solutions.sort();
+            assert_eq!(solutions, vec![vec![0, 1], vec![2]], "设置{}", setting);
+        }
+    }
+
+    /// 穷举所有非空子集，返回和落在 [lower, upper] 内的索引列表（排序后）
+    fn brute_force(numbers: &[i64], lower: i64, upper: i64) -> Vec<Vec<usize>> {
+        let mut solutions: Vec<Vec<usize>> = (1u64..1 << numbers.len())
+            .map(|mask| (0..numbers.len()).filter(|&i| mask >> i & 1 == 1).collect::<Vec<_>>())
+            .filter(|s| (lower..=upper).contains(&s.iter().map(|&i| numbers[i]).sum::<i64>()))
+            .collect();
+        solutions.sort();
+        solutions
+    }
+
+    #[test]
+    fn mixed_sign_inputs_find_every_subset() {
+        let solver = SubsetSumSolver::new();
+        // 小规模走位运算，解要经过负的部分和
+        let numbers = [-5, 10, 3, -2];
+        let mut solutions = exact(&numbers, 8, solver.solve_int(&numbers, 8, 10, 0));
+        solutions.sort();
+        assert_eq!(solutions, vec![vec![0, 1, 2], vec![1, 3]]);
+        assert_eq!(*lock(&solver.last_strategy), "bitmask");
+        
+        // 超过位运算规模时走有符号回溯；穿插的大数无法被负数抵消，解与只含前8个数时相同
+        let head = [-5, 10, 3, -2, 7, -9, 4, -1];
+        let mut numbers = head.to_vec();
+        numbers.extend((1..=30).map(|i| 1_000_000_000 * i));
+        let mut solutions = solver.solve_int(&numbers, 8, usize::MAX, 0);
+        assert_eq!(*lock(&solver.last_strategy), "signed_backtracking");
+        solutions.sort();
+        assert_eq!(solutions, brute_force(&head, 8, 8));
+    }
+
+    #[test]
+    fn all_negative_inputs_reach_a_negative_target() {
+        let solver = SubsetSumSolver::new();
+        let numbers = [-4, -7, -1, -3, -6];
+        let mut solutions = exact(&numbers, -8, solver.solve_int(&numbers, -8, 10, 0));
+        solutions.sort();
+        assert_eq!(solutions, brute_force(&numbers, -8, -8));
+        assert!(!solutions.is_empty());
+        
+        // -1..=-36：-3 只能由 [-3] 或 [-1, -2] 组成
+        let numbers: Vec<i64> = (1..=36).map(|i| -i).collect();
+        let mut solutions = solver.solve_int(&numbers, -3, 10, 0);
+        assert_eq!(*lock(&solver.last_strategy), "signed_backtracking");
+        solutions.sort();
+        assert_eq!(solutions, vec![vec![0, 1], vec![2]]);
+    }
+
+    #[test]
+    fn non_negative_inputs_keep_the_unsigned_algorithms() {
+        let solver = SubsetSumSolver::new();
+        let numbers = [3, 34, 4, 12, 5, 2];
+        assert_eq!(exact(&numbers, 9, solver.solve_int(&numbers, 9, 10, 0)).len(), 2);
+        assert_eq!(*lock(&solver.last_strategy), "bitmask");
+        
+        let numbers: Vec<i64> = (1..=40).collect();
+        let solutions = exact(&numbers, 30, solver.solve_int(&numbers, 30, 5, 0));
+        assert_eq!(*lock(&solver.last_strategy), "dp");
+        assert!(solutions.contains(&vec![29]) && solutions.len() >= 2); // 单元素预扫描加动态规划的解
+        
+        let numbers: Vec<i64> = (0..40).map(|i| 100_003 + 7_919 * i * i).collect();
+        let target = numbers[1] + numbers[5] + numbers[9];
+        let mut solutions = solver.solve_int(&numbers, target, 10, 0);
+        solutions.iter_mut().for_each(|s| s.sort_unstable());
+        assert_eq!(solutions, vec![vec![1, 5, 9]]);
+        assert_eq!(*lock(&solver.last_strategy), "backtracking");
+        
+        // 全为非负数时负目标直接判定不可达
+        assert!(solver.solve_int(&numbers, -1, 10, 0).is_empty());
+        assert_eq!(*lock(&solver.last_strategy), "infeasible");
+    }
+
+    #[test]
+    fn window_queries_return_only_in_window_subsets() {
+        let in_window = |numbers: &[i64], solutions: &[Vec<usize>], lower: i64, upper: i64| {
+            solutions.iter().all(|s| (lower..=upper).contains(&s.iter().map(|&i| numbers[i]).sum::<i64>()))
+        };
+        let mut solver = SubsetSumSolver::new();
+        
+        // 位运算：区间内有多个解，结果与穷举一致
+        let numbers = [3, 34, 4, 12, 5, 2, 7, 9];
+        let mut solutions = solver.solve_window(&numbers, 14, 16, usize::MAX, 0);
+        assert_eq!(*lock(&solver.last_strategy), "bitmask");
+        solutions.sort();
+        assert_eq!(solutions, brute_force(&numbers, 14, 16));
+        assert!(solutions.len() > 10);
+        // 区间内的解不足max_solutions时也不补充区间外的组合
+        assert!(solver.solve_window(&[5, 10, 20], 1, 4, 10, 0).is_empty());
+        
+        // 回溯（合并重复值时使用）：同样与穷举一致
+        solver.merge_duplicates = true;
+        let mut solutions = solver.solve_window(&numbers, 14, 16, usize::MAX, 0);
+        assert_eq!(*lock(&solver.last_strategy), "backtracking");
+        solutions.iter_mut().for_each(|s| s.sort_unstable());
+        solutions.sort();
+        assert_eq!(solutions, brute_force(&numbers, 14, 16));
+        solver.merge_duplicates = false;
+        
+        // 动态规划：区间内每个可达的和各一个解，不补充区间外的和
+        let numbers: Vec<i64> = (0..40).map(|i| 4 * (3 + i)).collect();
+        let solutions = solver.solve_window(&numbers, 100, 110, usize::MAX, 0);
+        assert_eq!(*lock(&solver.last_strategy), "dp");
+        assert!(in_window(&numbers, &solutions, 100, 110));
+        let mut sums: Vec<i64> = solutions.iter().map(|s| s.iter().map(|&i| numbers[i]).sum()).collect();
+        sums.sort_unstable();
+        sums.dedup();
+        assert_eq!(sums, vec![100, 104, 108]);
+        // 区间内没有可达的和（都是4的倍数）时返回空结果
+        assert!(solver.solve_window(&numbers, 13, 15, 10, 0).is_empty());
+        assert_eq!(*lock(&solver.last_strategy), "dp");
+    }
+
+    #[test]
+    fn float_tolerance_keeps_results_inside_the_window() {
+        pyo3::prepare_freethreaded_python();
+        let solver = SubsetSumSolver::new();
+        let numbers = vec![10.01, 9.99, 5.0, 5.02, 4.98, 20.0, 30.0];
+        let sums = |solutions: &[Vec<usize>]| -> Vec<f64> {
+            solutions.iter().map(|s| s.iter().map(|&i| numbers[i]).sum()).collect()
+        };
+        let solutions = Python::with_gil(|py| solver.find_subsets_float(py, numbers.clone(), 20.0, Some(100), None, None, Some(0.03))).unwrap();
+        assert!(solutions.len() > 3);
+        assert!(sums(&solutions).iter().all(|sum| (sum - 20.0).abs() <= 0.03 + 1e-9));
+        assert!(solutions.contains(&vec![5]) && solutions.contains(&vec![0, 1]) && solutions.contains(&vec![0, 3, 4]));
+        
+        // decimals=1时容差0.05被截断为0：退化为精确求解，但仍不返回区间外的近似组合
+        let solutions = Python::with_gil(|py| solver.find_subsets_float(py, vec![10.0, 5.0, 2.0], 20.0, Some(10), None, Some(1), Some(0.05))).unwrap();
+        assert!(solutions.is_empty());
+    }
+
+    #[test]
+    fn ints_match_the_float_path_on_integer_inputs() {
+        pyo3::prepare_freethreaded_python();
+        let solver = SubsetSumSolver::new();
+        let cases: Vec<(Vec<i64>, i64)> = vec![
+            (vec![3, 34, 4, 12, 5, 2], 9),                               // 位运算，会补充近似解
+            (vec![3, 34, 4, 12, 5, 2], 1000),                            // 无精确解
+            ((1..=40).collect(), 30),                                     // 动态规划
+            ((0..40).map(|i| 1_000_000_000 + 7_919 * i * i).collect(), 3_000_000_000 + 7_919 * (1 + 25 + 81)),
+        ];
+        for (numbers, target) in cases {
+            let (ints, floats) = Python::with_gil(|py| {
+                let ints = solver.find_subsets_ints(py, numbers.clone(), target, Some(10)).unwrap();
+                let floats = solver.find_subsets_float(py, numbers.iter().map(|&x| x as f64).collect(), target as f64, Some(10), None, None, None).unwrap();
+                (ints, floats)
+            });
+            assert!(ints.iter().all(|values| values.iter().sum::<i64>() == target), "目标{}", target);
+            let expected: Vec<Vec<i64>> = exact(&numbers, target, floats).into_iter()
+                .map(|s| s.into_iter().map(|i| numbers[i]).collect())
+                .collect();
+            assert_eq!(ints, expected, "目标{}", target);
+        }
+    }
+
+    #[test]
+    fn subset_indices_keep_duplicate_values_apart() {
+        pyo3::prepare_freethreaded_python();
+        let mut solver = SubsetSumSolver::new();
+        let indices = |solver: &SubsetSumSolver, numbers: Vec<f64>, target: f64| {
+            let mut solutions = Python::with_gil(|py| solver.find_subset_indices(py, numbers, target, Some(100))).unwrap();
+            solutions.sort();
+            solutions
+        };
+        // 相同数值的不同位置是不同的解，合并重复值的设置不影响结果
+        for merge in [false, true] {
+            solver.merge_duplicates = merge;
+            assert_eq!(indices(&solver, vec![2.0, 2.0, 2.0, 3.0], 4.0), vec![vec![0, 1], vec![0, 2], vec![1, 2]]);
+        }
+        solver.merge_duplicates = false;
+        
+        // 索引映射回的数值之和恰好为目标，且与穷举一致（包括含负数的输入）
+        for (numbers, target) in [(vec![1.25, 0.5, 1.25, 0.75, 0.5, 2.0], 2.5), (vec![-1.5, 3.0, 1.5, 1.5, -3.0], 1.5)] {
+            let solutions = indices(&solver, numbers.clone(), target);
+            for solution in &solutions {
+                assert!((solution.iter().map(|&i| numbers[i]).sum::<f64>() - target).abs() < 1e-9);
+            }
+            let scaled: Vec<i64> = numbers.iter().map(|x| (x * 100.0).round() as i64).collect();
+            assert_eq!(solutions, brute_force(&scaled, (target * 100.0) as i64, (target * 100.0) as i64));
+        }
+        
+        // 0同样可以选入，与纯Python实现一致
+        assert_eq!(indices(&solver, vec![0.0, 2.0, 2.0], 2.0), vec![vec![0, 1], vec![0, 2], vec![1], vec![2]]);
+        
+        // 无精确解时不补充近似解
+        assert!(indices(&solver, vec![3.0, 5.0, 9.0], 7.0).is_empty());
+    }
+
+    #[test]
+    fn incomplete_results_are_not_cached() {
+        let mut solver = SubsetSumSolver::new();
+        solver.enable_cache(8);
+        solver.max_len = Some(60); // 走回溯，每个解单独登记内存
+        let numbers: Vec<i64> = (1..=60).collect();
+        
+        // 内存限额只够几个解：搜索提前停止，结果不写入缓存
+        solver.memory_tracker.max_memory = 4096;
+        let partial = solver.find_subsets_cached(&numbers, 100, usize::MAX, 0);
+        assert!(!partial.is_empty());
+        assert_eq!(solver.get_cache_stats().2, 0);
+        
+        // 自然结束的搜索写入缓存；内存限额是缓存键的一部分，改变后不会命中旧结果
+        solver.memory_tracker.max_memory = 1 << 30;
+        let complete = solver.find_subsets_cached(&numbers, 100, 5, 0);
+        assert_eq!(complete.len(), 5);
+        assert_eq!(solver.get_cache_stats(), (0, 2, 1));
+        solver.memory_tracker.max_memory = 1 << 29;
+        solver.find_subsets_cached(&numbers, 100, 5, 0);
+        assert_eq!(solver.get_cache_stats(), (0, 3, 2));
+        
+        // 部分解交给了解回调：返回值不完整，不缓存，再次求解时回调照常收到这些解
+        pyo3::prepare_freethreaded_python();
+        let received: PyObject = Python::with_gil(|py| pyo3::types::PyList::empty(py).into());
+        let callback: PyObject = Python::with_gil(|py| received.getattr(py, "extend").unwrap());
+        solver.set_solution_callback(Some(callback));
+        solver.memory_tracker.max_memory = 4096;
+        let (hits, _, entries) = solver.get_cache_stats();
+        for _ in 0..2 {
+            Python::with_gil(|py| received.call_method0(py, "clear")).unwrap();
+            let returned = solver.find_subsets_cached(&numbers, 100, 200, 0);
+            let delivered = Python::with_gil(|py| received.as_ref(py).len().unwrap());
+            assert!(delivered > 0);
+            assert_eq!(delivered + returned.len(), 200);
+        }
+        assert_eq!(solver.get_cache_stats().0, hits);
+        assert_eq!(solver.get_cache_stats().2, entries);
+    }
+
+    #[test]
+    fn solution_callback_error_is_returned_from_the_solve() {
+        pyo3::prepare_freethreaded_python();
+        let mut solver = SubsetSumSolver::new();
+        solver.max_len = Some(60);
+        solver.memory_tracker.max_memory = 4096; // 只够几个解，必然分批交给回调
+        let callback: PyObject = Python::with_gil(|py| py.eval("lambda chunk: 1 / 0", None, None).unwrap().into());
+        solver.set_solution_callback(Some(callback));
+        let numbers: Vec<i64> = (1..=60).collect();
+        Python::with_gil(|py| {
+            let err = solver.find_subsets(py, numbers.clone(), 100, Some(200), None).unwrap_err();
+            assert!(err.is_instance_of::<pyo3::exceptions::PyZeroDivisionError>(py));
+            let err = solver.find_subsets_fifo(py, numbers.clone(), 100, Some(200)).unwrap_err();
+            assert!(err.is_instance_of::<pyo3::exceptions::PyZeroDivisionError>(py));
+            // 异常只抛出一次，不影响之后的求解
+            solver.set_solution_callback(None);
+            solver.memory_tracker.max_memory = 1 << 30;
+            assert_eq!(solver.find_subsets(py, numbers.clone(), 100, Some(5), None).unwrap().len(), 5);
+        });
+    }
+
+    #[test]
+    fn complete_subset_returns_preselected_once() {
+        pyo3::prepare_freethreaded_python();
+        let mut solver = SubsetSumSolver::new();
+        let numbers = vec![4, 6, 1, 3, 2];
+        for allow_empty in [false, true] {
+            solver.allow_empty = allow_empty;
+            let solutions = Python::with_gil(|py| solver.complete_subset(py, numbers.clone(), vec![1, 0], 10, None)).unwrap();
+            assert_eq!(solutions, vec![vec![0, 1]], "allow_empty={}", allow_empty);
+            let mut solutions = Python::with_gil(|py| solver.complete_subset(py, numbers.clone(), vec![1], 10, None)).unwrap();
+            solutions.sort();
+            assert_eq!(solutions, vec![vec![0, 1], vec![1, 2, 3]], "allow_empty={}", allow_empty);
+            // 没有预选元素时，空集只在允许时作为解
+            let solutions = Python::with_gil(|py| solver.complete_subset(py, numbers.clone(), vec![], 0, None)).unwrap();
+            assert_eq!(solutions, if allow_empty { vec![vec![]] } else { Vec::<Vec<usize>>::new() });
+        }
+    }
+
+    #[test]
+    fn solve_within_keeps_exact_solutions_and_only_stops_its_own_solve() {
+        pyo3::prepare_freethreaded_python();
+        let mut solver = SubsetSumSolver::new();
+        solver.max_len = Some(60); // 走回溯：很快找到大量解，但远不能在预算内搜完
+        let numbers: Vec<i64> = (1..=60).collect();
+        let expected = SubsetSumSolver { max_len: Some(60), ..SubsetSumSolver::new() }.solve_int(&numbers, 90, usize::MAX, 0).len();
+        
+        let (within, other) = std::thread::scope(|scope| {
+            let (solver, numbers) = (&solver, &numbers);
+            let other = scope.spawn(move || solver.solve_int(numbers, 90, usize::MAX, 0).len());
+            let within = Python::with_gil(|py| solver.solve_within(py, numbers.clone(), 600, 20, Some(usize::MAX))).unwrap();
+            (within, other.join().unwrap())
+        });
+        // 超时后返回已找到的精确解，而不是贪心近似解
+        let (solutions, approximate) = within;
+        assert!(!approximate);
+        assert!(solutions.len() > 1);
+        assert!(solutions.iter().all(|s| s.iter().map(|&i| numbers[i]).sum::<i64>() == 600));
+        // 同一实例上的另一次求解不受超时影响
+        assert_eq!(other, expected);
+        
+        // 超时前一个精确解都没有找到时才返回贪心近似解（偶数凑不出奇数目标）
+        let evens: Vec<i64> = (1..=60).map(|i| 2 * i).collect();
+        let (solutions, approximate) = Python::with_gil(|py| solver.solve_within(py, evens, 1201, 20, None)).unwrap();
+        assert!(approximate);
+        assert_eq!(solutions.len(), 1);
+    }
+
+    #[test]
+    fn numeric_rejects_bools_and_keeps_large_ints_exact() {
+        pyo3::prepare_freethreaded_python();
+        let solver = SubsetSumSolver::new();
+        Python::with_gil(|py| {
+            let values = |code: &str| -> Vec<&PyAny> { py.eval(code, None, None).unwrap().extract().unwrap() };
+            let one = py.eval("1", None, None).unwrap();
+            for (numbers, target) in [("[True, 1, 2]", "2"), ("[1, 2, 3]", "True"), ("[1.5, False]", "1.5")] {
+                let err = solver.find_subsets_numeric(py, values(numbers), py.eval(target, None, None).unwrap(), None).unwrap_err();
+                assert_eq!(error_code(err), "BOOL_INPUT", "{} {}", numbers, target);
+            }
+            // 整数路径保持完整精度（超过2^53）
+            let numbers = values("[2**60 + 1, 2**60, 1]");
+            let target = py.eval("2**60 + 1", None, None).unwrap();
+            let ints = [(1 << 60) + 1, 1 << 60, 1];
+            let mut solutions = exact(&ints, ints[0], solver.find_subsets_numeric(py, numbers, target, None).unwrap());
+            solutions.sort();
+            assert_eq!(solutions, vec![vec![0], vec![1, 2]]);
+            assert_eq!(exact(&[1, 2], 1, solver.find_subsets_numeric(py, values("[1, 2]"), one, None).unwrap()), vec![vec![0]]);
+        });
+    }
+
+    #[test]
+    fn bitmask_indices_point_at_the_original_values() {
+        // 数值互不相同且未排序：每个返回的索引必须正好指向期望的数值（第i位对应numbers[i]）
+        let numbers = [17, 3, 42, 8, 25, 1];
+        let solver = SubsetSumSolver::new();
+        for (target, expected) in [(26, vec![vec![1, 8, 17], vec![1, 25]]), (42, vec![vec![17, 25], vec![42]]), (4, vec![vec![1, 3]])] {
+            let solutions = exact(&numbers, target, solver.find_subsets_with_bit(&numbers, target, target, 10));
+            let mut values: Vec<Vec<i64>> = solutions.iter()
+                .map(|s| {
+                    assert!(s.windows(2).all(|pair| pair[0] < pair[1]), "索引应为升序");
+                    let mut values: Vec<i64> = s.iter().map(|&i| numbers[i]).collect();
+                    values.sort_unstable();
+                    values
+                })
+                .collect();
+            values.sort();
+            assert_eq!(values, expected, "目标{}", target);
+        }
+        // 单个元素的解直接对应其原始位置
+        assert!(exact(&numbers, 42, solver.find_subsets_with_bit(&numbers, 42, 42, 10)).contains(&vec![2]));
+        assert_eq!(exact(&numbers, 8, solver.find_subsets_with_bit(&numbers, 8, 8, 10)), vec![vec![3]]);
+    }
+
+    #[test]
+    fn improvement_callback_error_is_returned_from_the_partition() {
+        pyo3::prepare_freethreaded_python();
+        let mut solver = SubsetSumSolver::new();
+        let numbers = vec![8, 7, 6, 5, 4];
+        // 第二次报告时抛出异常：动态规划随即停止，不再调用回调
+        let (calls, callback): (PyObject, PyObject) = Python::with_gil(|py| {
+            let calls = pyo3::types::PyList::empty(py);
+            let globals = pyo3::types::PyDict::new(py);
+            globals.set_item("calls", calls).unwrap();
+            let callback = py.eval("lambda group, diff: calls.append(diff) or (1 / 0 if len(calls) == 2 else None)", Some(globals), None).unwrap();
+            (calls.into(), callback.into())
+        });
+        solver.set_improvement_callback(Some(callback));
+        Python::with_gil(|py| {
+            let err = solver.find_balanced_partition(py, numbers.clone()).unwrap_err();
+            assert!(err.is_instance_of::<pyo3::exceptions::PyZeroDivisionError>(py));
+            assert_eq!(calls.as_ref(py).len().unwrap(), 2);
+        });
+        // 取消回调后正常求解
+        solver.set_improvement_callback(None);
+        let (_, _, diff, approx) = Python::with_gil(|py| solver.find_balanced_partition(py, numbers)).unwrap();
+        assert_eq!((diff, approx), (0, false));
+    }
+
+    #[test]
+    fn mask_bytes_rows_match_find_subsets() {
+        pyo3::prepare_freethreaded_python();
+        let solver = SubsetSumSolver::new();
+        let numbers = vec![3, 34, 4, 12, 5, 2];
+        Python::with_gil(|py| {
+            let solutions = solver.find_subsets(py, numbers.clone(), 9, None, None).unwrap();
+            let (data, rows, cols) = solver.find_subsets_mask_bytes(py, numbers.clone(), 9, None).unwrap();
+            let data: Vec<u8> = data.extract(py).unwrap();
+            assert_eq!((rows, cols), (solutions.len(), numbers.len()));
+            for (row, solution) in data.chunks(cols).zip(&solutions) {
+                let selected: Vec<usize> = (0..cols).filter(|&j| row[j] == 1).collect();
+                assert_eq!(&selected, solution);
+            }
+        });
+    }
+
+    #[test]
+    fn memory_budget_holds_under_concurrent_solves() {
+        // 同一实例上8个线程同时求解（回溯登记解，动态规划登记表），共享1MB限额，重复多轮检查是否漂移
+        let mut solver = SubsetSumSolver::new();
+        solver.set_memory_limit(1);
+        solver.max_len = Some(60); // 设置元素个数限制时不使用动态规划，解的数量远超限额
+        let limit = solver.memory_tracker.max_memory as u64;
+        let numbers: Vec<i64> = (1..=60).collect();
+        for _ in 0..5 {
+            std::thread::scope(|scope| {
+                for thread in 0..8 {
+                    let (solver, numbers) = (&solver, &numbers);
+                    scope.spawn(move || {
+                        if thread % 2 == 0 {
+                            solver.solve_int(numbers, 100, usize::MAX, 0);
+                        } else {
+                            solver.find_subsets_with_dp(numbers, 30_000, 30_000, 10);
+                        }
+                    });
+                }
+            });
+            assert!(solver.memory_tracker.peak_memory.load(Ordering::SeqCst) <= limit);
+            assert_eq!(solver.memory_tracker.get_used_memory(), 0);
+        }
+        // 限额确实被占满过，上面的检查不是空转
+        assert!(solver.memory_tracker.peak_memory.load(Ordering::SeqCst) > limit / 2);
+    }
+
+    #[test]
+    fn dp_skips_targets_whose_table_size_overflows_usize() {
+        let solver = SubsetSumSolver::new();
+        // 64位平台上i64::MAX能转换为usize，但表的字节数溢出；32位平台上转换本身失败
+        assert_eq!(solver.dp_table_bytes(i64::MAX), None);
+        assert!(solver.dp_table_bytes(1_000).is_some());
+        // 直接调用时提前返回，不分配表也不登记内存
+        assert!(solver.find_subsets_with_dp(&[1, 2, 3], i64::MAX, i64::MAX, 1).is_empty());
+        assert_eq!(solver.memory_tracker.peak_memory.load(Ordering::SeqCst), 0);
+        // 算法选择不会选中动态规划
+        assert_ne!(solver.select_algorithm(&(1..=30).collect::<Vec<i64>>(), i64::MAX), Algorithm::DynamicProgramming);
+    }
+
+    #[test]
+    fn scorer_callback_error_is_returned_from_the_solve() {
+        pyo3::prepare_freethreaded_python();
+        let callback: PyObject = Python::with_gil(|py| py.eval("lambda xs: 1 / 0", None, None).unwrap().into());
+        let mut ranked = RankedSolutions::new(Scorer::Callback { values: vec![1, 2, 3], callback }, 10);
+        assert!(!ranked.add(vec![0, 1]));
+        let err = ranked.into_sorted().unwrap_err();
+        Python::with_gil(|py| assert!(err.is_instance_of::<pyo3::exceptions::PyZeroDivisionError>(py)));
+    }
+
+    #[test]
+    fn knapsack_ranks_every_item_of_equal_weight() {
+        pyo3::prepare_freethreaded_python();
+        let solver = SubsetSumSolver::new();
+        // 重量相同的0号与1号价值不同，两种装法都应出现在结果中
+        let ranked = Python::with_gil(|py| solver.find_subsets_knapsack(py, vec![3, 3, 2, 4], vec![1.0, 5.0, 2.0, 0.5], 5, Some(10))).unwrap();
+        assert_eq!(ranked, vec![(vec![1, 2], 7.0), (vec![0, 2], 3.0)]);
+        // 偏好排序仍只保留相同数值中偏好最高的组合
+        let preferred = Python::with_gil(|py| solver.find_subsets_by_preference(py, vec![3, 3, 2, 4], vec![1.0, 5.0, 2.0, 0.5], 5, Some(10), None)).unwrap();
+        assert_eq!(preferred, vec![(vec![1, 2], 7.0)]);
+    }
+
+    #[test]
+    fn compact_subset_add_remove_contains_and_indices() {
+        let mut subset = CompactSubset::new();
+        assert_eq!(subset.len(), 0);
+        assert!(subset.to_indices().is_empty());
+        // 跨越64位块边界，重复添加不改变计数
+        for index in [130, 0, 63, 64, 5, 64] {
+            subset.add(index);
+        }
+        assert_eq!(subset.len(), 5);
+        assert_eq!(subset.to_indices(), vec![0, 5, 63, 64, 130]);
+        assert!(subset.contains(63) && subset.contains(64) && subset.contains(130));
+        assert!(!subset.contains(1) && !subset.contains(129) && !subset.contains(10_000));
+        // 删除不存在的索引（包括超出位图的）不改变计数
+        subset.remove(64);
+        subset.remove(64);
+        subset.remove(7);
+        subset.remove(10_000);
+        assert_eq!(subset.len(), 4);
+        assert_eq!(subset.to_indices(), vec![0, 5, 63, 130]);
+        assert!(!subset.contains(64));
+        subset.clear();
+        assert_eq!(subset.len(), 0);
+        assert!(subset.to_indices().is_empty() && !subset.contains(130));
+    }
+
+    #[test]
+    fn compact_subset_from_pool_is_empty() {
+        let mut subset = get_compact_subset_from_pool();
+        subset.add(3);
+        subset.add(200);
+        return_compact_subset_to_pool(subset);
+        let reused = get_compact_subset_from_pool();
+        assert_eq!(reused.len(), 0);
+        assert!(reused.to_indices().is_empty());
+    }
+
+    #[test]
+    fn min_breaking_set_rejects_negative_inputs() {
+        pyo3::prepare_freethreaded_python();
+        let solver = SubsetSumSolver::new();
+        Python::with_gil(|py| {
+            // 含负数时 -3 + 5 可以凑出2，位集判断会忽略负数，因此直接拒绝
+            let err = solver.min_breaking_set(py, vec![5, -3, 2], 2).unwrap_err();
+            assert_eq!(error_code(err), "NEGATIVE_INPUT");
+            let err = solver.min_breaking_set(py, vec![5, -3, 2], -1).unwrap_err();
+            assert_eq!(error_code(err), "NEGATIVE_INPUT");
+            // 非负输入时负目标本来就不可达
+            assert_eq!(solver.min_breaking_set(py, vec![5, 3, 2], -1).unwrap(), Some(Vec::new()));
+            assert_eq!(solver.min_breaking_set(py, vec![5, 3, 2], 5).unwrap(), Some(vec![0, 1]));
+        });
+    }
+}
@@ -67,6 +67,11 @@ class PySubsetSumSolver:
     
     def find_subsets(self, numbers: List[float], target: float, max_solutions: int = 1, memory_limit_mb: int = 1000) -> List[List[float]]:
         """查找和为目标值的子集"""
+        solutions = self.find_subset_indices(numbers, target, max_solutions)
+        return [[numbers[i] for i in solution] for solution in solutions]
+    
+    def find_subset_indices(self, numbers: List[float], target: float, max_solutions: int = 1) -> List[List[int]]:
+        """查找和为目标值的子集，返回每个解所选元素的索引"""
         if not numbers:
             raise ValueError("输入数字列表不能为空")
         
@@ -107,8 +112,8 @@ class PySubsetSumSolver:
         
         # 继续搜索
         for i in range(start, len(numbers)):
-            # 选择当前数字
-            current_subset.append(numbers[i])
+            # 选择当前数字（记录索引，重复的数值也能区分）
+            current_subset.append(i)
             current_sum += numbers[i]
             
             # 继续递归
@@ -170,7 +175,26 @@ class SubsetSumSolver:
     
     def find_subsets(self, numbers: List[float], target: float, max_solutions: int = 1, memory_limit_mb: int = 1000) -> List[List[float]]:
         """查找和为目标值的子集"""
-        return self._solver.find_subsets(numbers, target, max_solutions, memory_limit_mb)
+        if isinstance(self._solver, PySubsetSumSolver):
+            return self._solver.find_subsets(numbers, target, max_solutions, memory_limit_mb)
+        # Rust实现的内存限制是实例设置，find_subsets的第4个参数是expected_solutions（预计解数量），不能传入内存限制
+        self._solver.set_memory_limit(memory_limit_mb)
+        return self._solver.find_subsets(numbers, target, max_solutions)
+    
+    def find_subset_indices(self, numbers: List[float], target: float, max_solutions: int = 1) -> List[List[int]]:
+        """查找和为目标值的子集，返回每个解所选元素在numbers中的索引（可区分重复的数值）"""
+        return self._solver.find_subset_indices(numbers, target, max_solutions)
+    
+    def find_subsets_mask(self, numbers: List[int], target: int, max_solutions: int = 10):
+        """查找子集，以NumPy二维布尔数组返回（行为解，列为输入索引）
+        
+        数组直接引用Rust返回的字节串，不再复制，因此是只读的；需要修改时先调用 .copy()
+        """
+        if not hasattr(self._solver, 'find_subsets_mask_bytes'):
+            raise NotImplementedError("纯Python实现不支持布尔掩码输出")
+        import numpy as np
+        data, rows, cols = self._solver.find_subsets_mask_bytes(numbers, target, max_solutions)
+        return np.frombuffer(data, dtype=np.bool_).reshape(rows, cols)
     
     def stop(self):
         """停止计算"""